@@ -0,0 +1,75 @@
+use std::{env, fs, path::Path};
+
+// generates the protocol version table from data/protocol_versions.csv
+// and picks the language files to embed
+fn main() {
+    language_files();
+
+    let source = "data/protocol_versions.csv";
+    println!("cargo:rerun-if-changed={}", source);
+
+    let csv = fs::read_to_string(source).expect("protocol version data is missing");
+
+    let mut rows = Vec::new();
+    for (index, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let columns: Vec<&str> = line.split(',').map(str::trim).collect();
+        let (protocol, version, snapshot, release_date) = match columns[..] {
+            [protocol, version, snapshot, release_date] => {
+                (protocol, version, snapshot, release_date)
+            }
+            _ => panic!("{}:{}: expected 4 columns", source, index + 1),
+        };
+
+        let protocol: i32 = protocol
+            .parse()
+            .unwrap_or_else(|_| panic!("{}:{}: invalid protocol number", source, index + 1));
+        let snapshot: bool = snapshot
+            .parse()
+            .unwrap_or_else(|_| panic!("{}:{}: invalid snapshot flag", source, index + 1));
+
+        rows.push((
+            protocol,
+            version.to_string(),
+            snapshot,
+            release_date.to_string(),
+        ));
+    }
+
+    // lookups take a contiguous slice per protocol number
+    rows.sort_by_key(|row| row.0);
+
+    let mut table = String::from("pub static PROTOCOL_VERSIONS: &[ProtocolVersion] = &[\n");
+    for (protocol, version, snapshot, release_date) in rows {
+        table.push_str(&format!(
+            "    ProtocolVersion {{ protocol: {}, version: {:?}, snapshot: {}, release_date: {:?} }},\n",
+            protocol, version, snapshot, release_date
+        ));
+    }
+    table.push_str("];\n");
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("protocol_versions.rs");
+    fs::write(out, table).unwrap();
+}
+
+// MINECRAFT_LANG_DIR points to the vanilla en_us.json and de_de.json, e.g. extracted from
+// the client jar and the asset index, otherwise the bundled subsets are embedded.
+// keys missing from the subsets show the component's fallback or the key, see the readme
+fn language_files() {
+    println!("cargo:rerun-if-env-changed=MINECRAFT_LANG_DIR");
+
+    let dir = env::var("MINECRAFT_LANG_DIR").unwrap_or_else(|_| "data/lang".to_string());
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("lang");
+    fs::create_dir_all(&out).unwrap();
+
+    for name in ["en_us.json", "de_de.json"] {
+        let source = Path::new(&dir).join(name);
+        println!("cargo:rerun-if-changed={}", source.display());
+
+        fs::copy(&source, out.join(name)).unwrap_or_else(|e| panic!("{}: {}", source.display(), e));
+    }
+}
@@ -0,0 +1,104 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Expr, Fields, Index};
+
+/// Implements `Packet`, `Encode` and `Decode` for a struct whose fields are all encodable,
+/// the fields are written and read in declaration order.
+///
+/// ```ignore
+/// #[derive(Packet)]
+/// #[packet(id = 0x01)]
+/// pub struct PingRequest {
+///     pub payload: i64,
+/// }
+/// ```
+#[proc_macro_derive(Packet, attributes(packet))]
+pub fn derive_packet(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn packet_id(input: &DeriveInput) -> syn::Result<Expr> {
+    let mut id = None;
+
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("packet")) {
+        attr.parse_nested_meta(|meta| match meta.path.is_ident("id") {
+            true => {
+                id = Some(meta.value()?.parse::<Expr>()?);
+                Ok(())
+            }
+            false => Err(meta.error("expected `id = <packet id>`")),
+        })?;
+    }
+
+    id.ok_or_else(|| syn::Error::new(input.span(), "missing #[packet(id = ...)] attribute"))
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let id = packet_id(&input)?;
+    let name = &input.ident;
+
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new(
+                input.span(),
+                "Packet can only be derived for structs",
+            ))
+        }
+    };
+
+    let (encode, decode) = match fields {
+        Fields::Named(fields) => {
+            let names: Vec<_> = fields.named.iter().map(|f| &f.ident).collect();
+
+            (
+                quote! { #( crate::minecraft::packet::types::Encode::encode(&self.#names, buf)?; )* },
+                quote! {
+                    Ok(Self {
+                        #( #names: crate::minecraft::packet::types::Decode::decode(reader)?, )*
+                    })
+                },
+            )
+        }
+        Fields::Unnamed(fields) => {
+            let indices: Vec<_> = (0..fields.unnamed.len()).map(Index::from).collect();
+            let decodes = fields
+                .unnamed
+                .iter()
+                .map(|_| quote! { crate::minecraft::packet::types::Decode::decode(reader)? });
+
+            (
+                quote! { #( crate::minecraft::packet::types::Encode::encode(&self.#indices, buf)?; )* },
+                quote! { Ok(Self( #( #decodes, )* )) },
+            )
+        }
+        Fields::Unit => (quote! {}, quote! { Ok(Self) }),
+    };
+
+    Ok(quote! {
+        impl crate::minecraft::packet::Packet for #name {
+            const PACKET_ID: crate::minecraft::varint::VarInt = crate::minecraft::varint::VarInt(#id);
+        }
+
+        impl crate::minecraft::packet::types::Encode for #name {
+            #[allow(unused_variables)]
+            fn encode(&self, buf: &mut Vec<u8>) -> std::io::Result<()> {
+                #encode
+                Ok(())
+            }
+        }
+
+        impl crate::minecraft::packet::types::Decode for #name {
+            #[allow(unused_variables)]
+            fn decode<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+                #decode
+            }
+        }
+    })
+}
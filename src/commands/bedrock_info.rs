@@ -0,0 +1,131 @@
+use serenity::{
+    all::{
+        CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+        CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+        CreateInteractionResponseMessage, EditInteractionResponse, ResolvedOption, ResolvedValue,
+    },
+    async_trait,
+};
+
+use crate::minecraft::{
+    bedrock::{self, BedrockClient},
+    legacy_format,
+    render::markdown::MarkdownRenderer,
+};
+
+use super::Command;
+
+pub struct BedrockInfoCommand;
+
+#[async_trait]
+impl Command for BedrockInfoCommand {
+    fn name(&self) -> &'static str {
+        "bedrockinfo"
+    }
+
+    fn register(&self) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description("Fetch a minecraft bedrock servers information")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "host",
+                    "The servers IP/Hostname",
+                )
+                .required(true),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "port",
+                    "The servers game port",
+                )
+                .min_int_value(u16::MIN as u64)
+                .max_int_value(u16::MAX as u64)
+                .required(false),
+            )
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &CommandInteraction,
+    ) -> serenity::Result<Option<CreateInteractionResponse>> {
+        let options = command.data.options();
+
+        let (host, port) = match options.first() {
+            Some(ResolvedOption {
+                value: ResolvedValue::String(host),
+                ..
+            }) => {
+                let split_host: Vec<&str> = host.split(":").collect();
+
+                match split_host.get(1) {
+                    Some(port) => (split_host[0], port.parse::<u16>().ok()),
+                    None => (*host, None),
+                }
+            }
+            _ => {
+                return Ok(Some(CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new().content("Please provide a host"),
+                )))
+            }
+        };
+
+        let port = match port {
+            Some(port) => port,
+            None => match options.iter().find(|o| o.name == "port") {
+                Some(ResolvedOption {
+                    value: ResolvedValue::Integer(port),
+                    ..
+                }) => *port as u16,
+                _ => bedrock::DEFAULT_PORT,
+            },
+        };
+
+        // defer
+        command.defer(&ctx.http).await?;
+
+        let client = BedrockClient::new(host.to_string(), port).await?;
+        let (info, latency) = client.status_with_latency().await?;
+
+        let mut motd = info.motd;
+        if let Some(sub_motd) = info.sub_motd {
+            motd.push('\n');
+            motd.push_str(&sub_motd);
+        }
+        // discord rejects the reply once a field is longer than 1024 characters
+        let motd = MarkdownRenderer::new()
+            .max_length(1024)
+            .render(&legacy_format::parse(&motd));
+
+        let mut embed = CreateEmbed::new()
+            .title(format!("{}:{}", host, port))
+            .field("MOTD", motd, false)
+            .field("Players", format!("{}/{}", info.online, info.max), false)
+            .field("Version", info.version, true)
+            .field("Protocol", info.protocol.to_string(), true)
+            .field("Latency", format!("{}ms", latency.as_millis()), true)
+            .field("Edition", info.edition, true);
+
+        if let Some(gamemode) = info.gamemode {
+            embed = embed.field("Gamemode", gamemode, true);
+        }
+
+        let ports = [("IPv4", info.port_v4), ("IPv6", info.port_v6)]
+            .iter()
+            .filter_map(|(family, port)| port.map(|p| format!("{}: {}", family, p)))
+            .collect::<Vec<_>>();
+        if !ports.is_empty() {
+            embed = embed.field("Ports", ports.join("\n"), true);
+        }
+
+        embed = embed.footer(CreateEmbedFooter::new("helferbiene-rs"));
+
+        command
+            .edit_response(&ctx.http, EditInteractionResponse::new().add_embed(embed))
+            .await?;
+
+        return Ok(None);
+    }
+}
@@ -1,4 +1,6 @@
+pub mod bedrock_info;
 pub mod ping;
+pub mod rcon;
 pub mod server_info;
 
 use serenity::{
@@ -18,4 +20,9 @@ pub trait Command: Send + Sync {
     ) -> serenity::Result<Option<CreateInteractionResponse>>;
 }
 
-pub const COMMANDS: &[&dyn Command] = &[&ping::PingCommand, &server_info::ServerInfoCommand];
+pub const COMMANDS: &[&dyn Command] = &[
+    &ping::PingCommand,
+    &server_info::ServerInfoCommand,
+    &bedrock_info::BedrockInfoCommand,
+    &rcon::RconCommand,
+];
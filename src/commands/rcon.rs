@@ -0,0 +1,166 @@
+use std::env;
+
+use serenity::{
+    all::{
+        CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+        CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
+        ResolvedOption, ResolvedValue, RoleId,
+    },
+    async_trait,
+};
+
+use crate::minecraft::rcon::{self, RconClient};
+
+use super::Command;
+
+// discord rejects messages longer than this
+const MAX_MESSAGE_LENGTH: usize = 2000;
+
+pub struct RconCommand;
+
+struct RconConfig {
+    host: String,
+    port: u16,
+    password: String,
+    roles: Vec<RoleId>,
+}
+
+impl RconConfig {
+    /// Reads `RCON_SERVER` (host[:port]), `RCON_PASSWORD` and `RCON_ROLES` (comma separated role ids).
+    fn from_env() -> Option<Self> {
+        let server = env::var("RCON_SERVER").ok()?;
+        let password = env::var("RCON_PASSWORD").ok()?;
+
+        let split: Vec<&str> = server.split(":").collect();
+        let (host, port) = match split.get(1) {
+            Some(port) => (split[0].to_string(), port.parse::<u16>().ok()?),
+            None => (server, rcon::DEFAULT_PORT),
+        };
+
+        let roles = env::var("RCON_ROLES")
+            .unwrap_or_default()
+            .split(",")
+            .filter_map(|r| r.trim().parse::<u64>().ok())
+            .map(RoleId::new)
+            .collect();
+
+        Some(Self {
+            host,
+            port,
+            password,
+            roles,
+        })
+    }
+}
+
+fn ephemeral_message(content: &str) -> CreateInteractionResponse {
+    CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .content(content)
+            .ephemeral(true),
+    )
+}
+
+fn format_output(output: &str) -> String {
+    // strip legacy formatting codes, they only render ingame
+    let mut cleaned = String::with_capacity(output.len());
+    let mut chars = output.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '§' => _ = chars.next(),
+            c => cleaned.push(c),
+        }
+    }
+
+    let cleaned = cleaned.trim().replace("```", "`\u{200B}``");
+    if cleaned.is_empty() {
+        return "No output.".into();
+    }
+
+    let max_length = MAX_MESSAGE_LENGTH - "```\n\n```".len() - "...".len();
+    let cleaned = match cleaned.char_indices().nth(max_length) {
+        Some((index, _)) => format!("{}...", &cleaned[..index]),
+        None => cleaned,
+    };
+
+    format!("```\n{}\n```", cleaned)
+}
+
+#[async_trait]
+impl Command for RconCommand {
+    fn name(&self) -> &'static str {
+        "rcon"
+    }
+
+    fn register(&self) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description("Run a command on the minecraft servers console")
+            .dm_permission(false)
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "command",
+                    "The console command",
+                )
+                .max_length(rcon::RconPacket::MAX_PAYLOAD_SIZE as u16)
+                .required(true),
+            )
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &CommandInteraction,
+    ) -> serenity::Result<Option<CreateInteractionResponse>> {
+        let config = match RconConfig::from_env() {
+            Some(config) => config,
+            None => return Ok(Some(ephemeral_message("RCON is not configured."))),
+        };
+
+        let allowed = match command.member {
+            Some(ref member) => member.roles.iter().any(|r| config.roles.contains(r)),
+            None => false,
+        };
+
+        if !allowed {
+            log::warn!(
+                "User {} ({}) tried to use /rcon without permission",
+                command.user.name,
+                command.user.id
+            );
+            return Ok(Some(ephemeral_message(
+                "You are not allowed to use this command.",
+            )));
+        }
+
+        let console_command = match command.data.options().first() {
+            Some(ResolvedOption {
+                value: ResolvedValue::String(console_command),
+                ..
+            }) => console_command.to_string(),
+            _ => return Ok(Some(ephemeral_message("Please provide a command"))),
+        };
+
+        // defer
+        command.defer_ephemeral(&ctx.http).await?;
+
+        log::info!(
+            "User {} ({}) runs rcon command: {}",
+            command.user.name,
+            command.user.id,
+            console_command
+        );
+
+        let mut client = RconClient::connect(&config.host, config.port, &config.password).await?;
+        let output = client.command(&console_command).await?;
+
+        command
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(format_output(&output)),
+            )
+            .await?;
+
+        return Ok(None);
+    }
+}
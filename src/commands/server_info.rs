@@ -1,6 +1,6 @@
-use image::EncodableLayout;
+use std::time::Duration;
 
-use itertools::Itertools;
+use image::EncodableLayout;
 use serenity::{
     all::{
         Colour, CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
@@ -11,8 +11,18 @@ use serenity::{
 };
 
 use crate::minecraft::{
-    self,
-    packet::slp::{SlpResponse, SlpServerDescription},
+    client::{Client, DisconnectReason, LoginProbe, StatusMode},
+    fingerprint::ServerFingerprint,
+    legacy_format,
+    packet::slp::SlpResponse,
+    protocol_version,
+    query::QueryClient,
+    render::{
+        discord::DiscordAnsiRenderer,
+        markdown::{self, MarkdownRenderer},
+        png::{PngRenderer, ServerListEntry},
+    },
+    translation::Language,
 };
 
 use super::Command;
@@ -62,10 +72,9 @@ impl Command for ServerInfoCommand {
             }) => {
                 let split_host: Vec<&str> = host.split(":").collect();
 
-                if split_host.len() >= 1 {
-                    (split_host[0], split_host[0].parse::<u16>().ok())
-                } else {
-                    (*host, None)
+                match split_host.get(1) {
+                    Some(port) => (split_host[0], port.parse::<u16>().ok()),
+                    None => (*host, None),
                 }
             }
             _ => {
@@ -89,69 +98,207 @@ impl Command for ServerInfoCommand {
         // defer
         command.defer(&ctx.http).await?;
 
-        let client = minecraft::client::Client::new(host.to_string(), port).await?;
-        let info: SlpResponse = client.status().await?;
+        let client = Client::builder(host)
+            .port(port)
+            .mode(StatusMode::Auto)
+            .build()
+            .await?;
+        let (info, latency): (SlpResponse, _) = client.status_with_latency().await?;
+
+        // servers with enable-query=true answer on the game port unless query.port is changed
+        let (query_host, query_port) = match info.srv_target {
+            Some(ref target) => (target.host.as_str(), target.port),
+            None => (host, port),
+        };
+        let query = match QueryClient::new(query_host.to_string(), query_port).await {
+            Ok(client) => client.timeout(Duration::from_millis(500)).full_stat().await,
+            Err(e) => Err(e),
+        };
+        let query = match query {
+            Ok(query) => Some(query),
+            Err(e) => {
+                log::debug!("Query of {}:{} failed: {}", query_host, query_port, e);
+                None
+            }
+        };
 
-        let description = match info.description {
-            SlpServerDescription::Simple(ref description) => description.clone(),
-            SlpServerDescription::Complex(ref component) => component.format_string(),
+        let auth = match client.probe_login_as(info.version.protocol).await {
+            Ok(probe) => Some(probe),
+            Err(e) => {
+                log::debug!("Login probe of {}:{} failed: {}", host, port, e);
+                None
+            }
         };
 
+        let fingerprint =
+            ServerFingerprint::analyze(&info, latency, query.as_ref(), info.legacy.as_ref());
+
+        let language = Language::from_locale(&command.locale);
+
+        let motd = info.description.to_component();
+        let description = MarkdownRenderer::new()
+            .language(language)
+            .max_length(FIELD_LENGTH)
+            .render(&motd);
+
         let favicon = info.favicon;
 
-        let mut embed = CreateEmbed::new()
-            .title(format!("{}:{}", host, port))
-            .field("MOTD", description, false)
-            .field(
+        let mut protocol = match protocol_version::version_range(info.version.protocol) {
+            Some(range) => format!("{} → {}", info.version.protocol, range),
+            None => info.version.protocol.to_string(),
+        };
+        if !protocol_version::matches_name(info.version.protocol, &info.version.name) {
+            protocol.push_str("\n⚠️ doesn't match the reported version");
+        }
+
+        // version names can carry formatting codes, servers put whole banners there
+        let version = MarkdownRenderer::new()
+            .language(language)
+            .max_length(FIELD_LENGTH)
+            .render(&legacy_format::parse(&info.version.name));
+
+        let auth = auth.map(|auth| match auth {
+            LoginProbe::Disconnected {
+                reason: DisconnectReason::Other,
+                ref message,
+            } => format!(
+                "refused: {}",
+                MarkdownRenderer::new()
+                    .language(language)
+                    .max_length(FIELD_LENGTH - "refused: ".len())
+                    .render(&legacy_format::expand(message))
+            ),
+            _ => auth.to_string(),
+        });
+
+        let mut software = fingerprint.to_string();
+        if !fingerprint.hints.is_empty() {
+            software.push_str(&format!("\n*{}*", fingerprint.hints.join(", ")));
+        }
+
+        let title = format!("{}:{}", host, port);
+        let srv = info
+            .srv_target
+            .as_ref()
+            .map(|target| format!("resolved via SRV to {}:{}", target.host, target.port));
+        let players = format!("{}/{}", info.players.online, info.players.max);
+        let latency_ms = format!("{}ms", latency.as_millis());
+
+        // the lists share whatever the fixed fields leave of the embed
+        let mut remaining = EMBED_LENGTH.saturating_sub(
+            [
+                title.as_str(),
+                srv.as_deref().unwrap_or_default(),
+                "MOTD",
+                &description,
                 "Players",
-                format!("{}/{}", info.players.online, info.players.max),
-                false,
-            )
-            .footer(CreateEmbedFooter::new("helferbiene-rs"));
+                &players,
+                FOOTER,
+                "Version",
+                &version,
+                "Protocol",
+                &protocol,
+                "Latency",
+                &latency_ms,
+                "Auth",
+                auth.as_deref().unwrap_or_default(),
+                "Software",
+                &software,
+            ]
+            .iter()
+            .map(|text| text.chars().count())
+            .sum(),
+        );
 
-        if let Some(sample) = info.players.sample {
-            if !sample.is_empty() {
-                let all_players = sample.len() == info.players.online as usize;
+        let mut embed = CreateEmbed::new().title(title);
 
-                let mut formatted = sample
-                    .iter()
-                    .map(|p| format!("[{}](https://namemc.com/search?q={})", p.name, p.id))
-                    .join("\n");
+        if let Some(srv) = srv {
+            embed = embed.description(srv);
+        }
 
-                if !all_players {
-                    formatted.push_str("\n...");
-                }
+        embed = embed
+            .field("MOTD", description, false)
+            .field("Players", players, false)
+            .footer(CreateEmbedFooter::new(FOOTER));
+
+        if let Some(players) = query.as_ref().map(|q| &q.players).filter(|p| !p.is_empty()) {
+            let formatted: Vec<String> = players.iter().map(|p| namemc_link(p, p)).collect();
+
+            embed = list_field(embed, &mut remaining, "", &formatted, 0);
+        } else if let Some(sample) = info.players.sample {
+            if !sample.is_empty() {
+                // the sample is only a part of the online players
+                let hidden = usize::try_from(info.players.online)
+                    .unwrap_or_default()
+                    .saturating_sub(sample.len());
 
-                embed = embed.field("", &formatted, false);
+                let formatted: Vec<String> =
+                    sample.iter().map(|p| namemc_link(&p.name, &p.id)).collect();
+
+                embed = list_field(embed, &mut remaining, "", &formatted, hidden);
             }
         }
 
-        embed = embed.field("Version", info.version.name, true).field(
-            "Protocol",
-            info.version.protocol.to_string(),
-            true,
-        );
+        embed = embed
+            .field("Version", version, true)
+            .field("Protocol", protocol, true)
+            .field("Latency", latency_ms, true);
+
+        if let Some(auth) = auth {
+            embed = embed.field("Auth", auth, true);
+        }
+
+        embed = embed.field("Software", software, true);
 
-        if let Some(mod_info) = info.modinfo {
-            if mod_info.type_ == "FML" && !mod_info.mod_list.is_empty() {
-                let formatted = mod_info
+        if let Some(plugins) = query.map(|q| q.plugins.plugins).filter(|p| !p.is_empty()) {
+            embed = list_field(embed, &mut remaining, "Plugins", &plugins, 0);
+        }
+
+        let mut mods: Vec<String> = Vec::new();
+        let mut mods_truncated = false;
+        let mut channels: Vec<String> = Vec::new();
+        if let Some(mod_info) = info.modinfo.filter(|m| m.type_ == "FML") {
+            mods.extend(
+                mod_info
                     .mod_list
                     .iter()
-                    .map(|m| format!("{}@{}", m.modid, m.version))
-                    .join("\n");
-
-                let chunks = split_into_chunks(formatted.as_str(), 1024);
-                for (index, chunk) in chunks.iter().enumerate() {
-                    let field_name = match index {
-                        0 => "Mods",
-                        _ => "",
-                    };
-                    embed = embed.field(field_name, chunk, false);
-                }
-            }
+                    .map(|m| format!("{}@{}", m.modid, m.version)),
+            );
+        }
+
+        if let Some(forge_data) = info.forge_data {
+            mods.extend(forge_data.mods.iter().map(|m| match m.version() {
+                Some(version) => format!("{}@{}", m.mod_id, version),
+                None => format!("{} (server only)", m.mod_id),
+            }));
+
+            // the server drops entries once the status gets too big
+            mods_truncated = forge_data.truncated;
+
+            channels = forge_data
+                .channels
+                .iter()
+                .map(|c| match c.required {
+                    true => format!("{}@{} (required)", c.res, c.version),
+                    false => format!("{}@{}", c.res, c.version),
+                })
+                .collect();
+        }
+
+        // modpacks list hundreds of mods and channels
+        if !mods.is_empty() {
+            let name = match mods_truncated {
+                true => "Mods (truncated by the server)",
+                false => "Mods",
+            };
+            embed = list_field(embed, &mut remaining, name, &mods, 0);
+        }
+        if !channels.is_empty() {
+            embed = list_field(embed, &mut remaining, "Channels", &channels, 0);
         }
 
         let mut response = EditInteractionResponse::new();
+        let mut favicon_image = None;
 
         if let Some(favicon) = favicon {
             let favicon = match favicon.strip_prefix("data:image/png;base64,") {
@@ -177,6 +324,8 @@ impl Command for ServerInfoCommand {
                         }
                         Err(e) => log::error!("Failed to extract dominant color: {}", e),
                     };
+
+                    favicon_image = Some(image);
                 }
 
                 let attachment = CreateAttachment::bytes(decoded, "favicon.png");
@@ -185,6 +334,34 @@ impl Command for ServerInfoCommand {
             }
         }
 
+        let entry = ServerListEntry {
+            name: host.to_string(),
+            motd,
+            online: info.players.online,
+            max: info.players.max,
+            latency: Some(latency),
+            favicon: favicon_image,
+        };
+        match PngRenderer::new().language(language).render(&entry) {
+            Ok(png) => {
+                response = response.new_attachment(CreateAttachment::bytes(png, "motd.png"));
+                embed = embed.image("attachment://motd.png");
+            }
+            Err(e) => {
+                log::error!("Failed to render the server list entry: {}", e);
+
+                // colors without the image
+                let preview = DiscordAnsiRenderer::new()
+                    .language(language)
+                    .render(&entry.motd);
+                if preview.len() <= FIELD_LENGTH
+                    && "Preview".len() + preview.chars().count() <= remaining
+                {
+                    embed = embed.field("Preview", preview, false);
+                }
+            }
+        }
+
         response = response.add_embed(embed);
 
         command.edit_response(&ctx.http, response).await?;
@@ -193,31 +370,158 @@ impl Command for ServerInfoCommand {
     }
 }
 
-fn split_into_chunks(input: &str, max_chunk_size: usize) -> Vec<String> {
-    let mut result = Vec::new();
-    let mut current_chunk = String::new();
-    let mut current_size = 0;
-
-    for line in input.lines() {
-        // Check if adding the line exceeds the max_chunk_size
-        if current_size + line.len() > max_chunk_size {
-            // If it exceeds, push the current chunk into the result
-            result.push(current_chunk.clone());
-            // Reset current_chunk and current_size
-            current_chunk.clear();
-            current_size = 0;
+// discord rejects the whole reply once a field has more than 1024 or the embed more than
+// 6000 characters, so every list gets a single field and the later lists get cut first
+const FIELD_LENGTH: usize = 1024;
+const EMBED_LENGTH: usize = 6000;
+const FOOTER: &str = "helferbiene-rs";
+
+/// Links a player to their namemc search, names come from the server and can contain markdown.
+fn namemc_link(name: &str, query: &str) -> String {
+    let url = reqwest::Url::parse_with_params("https://namemc.com/search", [("q", query)])
+        .expect("the base url is valid");
+    format!("[{}]({})", markdown::escape(name), url)
+}
+
+/// Adds a list as a field if it fits into the `remaining` characters of the embed.
+fn list_field(
+    embed: CreateEmbed,
+    remaining: &mut usize,
+    name: &str,
+    lines: &[String],
+    hidden: usize,
+) -> CreateEmbed {
+    let name_length = name.chars().count();
+    let max_length = FIELD_LENGTH.min(remaining.saturating_sub(name_length));
+
+    let list = capped_list(lines, hidden, max_length);
+    let length = name_length + list.chars().count();
+    if length > *remaining {
+        return embed;
+    }
+
+    *remaining -= length;
+    embed.field(name, list, false)
+}
+
+/// Joins as many lines as fit into `max_length` characters and counts the rest,
+/// `hidden` lines are already left out.
+fn capped_list(lines: &[String], hidden: usize, max_length: usize) -> String {
+    let more = |count: usize| format!("… and {} more", count);
+
+    let mut list = String::new();
+    let mut length = 0;
+
+    for (index, line) in lines.iter().enumerate() {
+        // a line has to leave room for counting everything after it
+        let rest = lines.len() - index - 1 + hidden;
+        let reserved = match rest {
+            0 => 0,
+            rest => more(rest).chars().count(),
+        };
+
+        let line_length = line.chars().count() + 1;
+        if length + line_length + reserved > max_length {
+            list.push_str(&more(rest + 1));
+            return list;
+        }
+
+        list.push_str(line);
+        list.push('\n');
+        length += line_length;
+    }
+
+    if hidden > 0 {
+        list.push_str(&more(hidden));
+    }
+
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caps_lists_to_one_field() {
+        let players: Vec<String> = (0..500).map(|i| format!("Player{:04}", i)).collect();
+
+        let list = capped_list(&players, 3, FIELD_LENGTH);
+        assert!(list.chars().count() <= FIELD_LENGTH);
+        let shown = list.lines().count() - 1;
+        assert_eq!(
+            list.lines().last(),
+            Some(format!("… and {} more", 500 - shown + 3).as_str())
+        );
+
+        assert_eq!(
+            capped_list(&players[..2], 0, FIELD_LENGTH),
+            "Player0000\nPlayer0001\n"
+        );
+    }
+
+    #[test]
+    fn reserves_room_for_large_counts() {
+        let players: Vec<String> = (0..500).map(|i| format!("Player{:04}", i)).collect();
+
+        for hidden in [i32::MAX as usize - 501, i32::MAX as usize, usize::MAX - 500] {
+            let list = capped_list(&players, hidden, FIELD_LENGTH);
+            assert!(list.chars().count() <= FIELD_LENGTH, "{}", hidden);
+
+            let shown = list.lines().count() - 1;
+            assert_eq!(
+                list.lines().last(),
+                Some(format!("… and {} more", 500 - shown + hidden).as_str())
+            );
         }
 
-        // Add the line to the current_chunk
-        current_chunk.push_str(line);
-        current_chunk.push('\n'); // Add back the newline character
-        current_size += line.len() + 1; // Add the length of the line plus 1 for the newline character
+        // every line fits exactly when nothing follows it
+        let exact = vec!["x".repeat(FIELD_LENGTH - 1)];
+        assert_eq!(
+            capped_list(&exact, 0, FIELD_LENGTH).chars().count(),
+            FIELD_LENGTH
+        );
+        assert_eq!(capped_list(&exact, 1, FIELD_LENGTH), "… and 2 more");
     }
 
-    // Push the last chunk
-    if !current_chunk.is_empty() {
-        result.push(current_chunk);
+    #[test]
+    fn cuts_later_lists_to_the_embed_length() {
+        let lines: Vec<String> = (0..500)
+            .map(|i| format!("some_mod_{:04}@1.0.0", i))
+            .collect();
+
+        let mut remaining = EMBED_LENGTH - 2500;
+        let mut embed = CreateEmbed::new();
+        for name in ["", "Plugins", "Mods", "Channels", "More"] {
+            embed = list_field(embed, &mut remaining, name, &lines, 0);
+        }
+
+        let json = serde_json::to_value(&embed).unwrap();
+        let fields = json["fields"].as_array().unwrap();
+        let length: usize = fields
+            .iter()
+            .map(|f| {
+                f["name"].as_str().unwrap().chars().count()
+                    + f["value"].as_str().unwrap().chars().count()
+            })
+            .sum();
+        assert!(length <= EMBED_LENGTH - 2500);
+        assert_eq!(length, EMBED_LENGTH - 2500 - remaining);
+
+        // the last list only gets what the others left
+        assert_eq!(fields.len(), 4);
+        assert!(fields[3]["value"].as_str().unwrap().chars().count() < FIELD_LENGTH / 2);
     }
 
-    result
+    #[test]
+    fn escapes_namemc_links() {
+        assert_eq!(
+            namemc_link("Notch", "069a79f4-44e9-4726-a5be-fca90e38aaf5"),
+            "[Notch](https://namemc.com/search?q=069a79f4-44e9-4726-a5be-fca90e38aaf5)"
+        );
+        assert_eq!(
+            namemc_link("a_b](x)", "a_b](x) &q=1"),
+            "[a\\_b\\]\\(x\\)](https://namemc.com/search?q=a_b%5D%28x%29+%26q%3D1)"
+        );
+    }
 }
@@ -1,6 +1,8 @@
 use std::io;
 
 use crate::commands::COMMANDS;
+use crate::minecraft::limits::LimitExceeded;
+use crate::minecraft::packet::state::UnknownPacket;
 use serenity::all::Color;
 use serenity::all::Command;
 use serenity::all::CreateEmbed;
@@ -58,6 +60,12 @@ impl EventHandler for Handler {
                     let description = match e {
                         SerenityError::Io(e) => match e.kind() {
                             io::ErrorKind::TimedOut => "Timed out.",
+                            _ if e.get_ref().is_some_and(|e| e.is::<LimitExceeded>()) => {
+                                "The server response exceeded the allowed limits."
+                            }
+                            _ if e.get_ref().is_some_and(|e| e.is::<UnknownPacket>()) => {
+                                "The server sent a packet that isn't part of the protocol."
+                            }
                             _ => "An error occured while executing the command.",
                         },
                         _ => "An error occured while executing the command.",
@@ -23,10 +23,9 @@ async fn main() -> std::io::Result<()> {
     if let Ok(activity_server) = env::var("ACTIVITY_SERVER") {
         let split: Vec<&str> = activity_server.split(":").collect();
 
-        let (host, port) = if split.len() >= 1 {
-            (split[0].to_string(), split[1].parse::<u16>().unwrap())
-        } else {
-            (activity_server, 25565)
+        let (host, port) = match split.get(1) {
+            Some(port) => (split[0].to_string(), port.parse::<u16>().unwrap()),
+            None => (activity_server, 25565),
         };
 
         let shard_manager = client.shard_manager.clone();
@@ -30,8 +30,8 @@ impl Activity {
         loop {
             interval.tick().await;
 
-            let info = match self.client.status().await {
-                Ok(info) => info,
+            let (info, latency) = match self.client.status_with_latency().await {
+                Ok(res) => res,
                 Err(e) => {
                     log::error!("Failed to fetch status: {}", e);
                     continue;
@@ -40,8 +40,10 @@ impl Activity {
 
             if let Err(e) = self
                 .update_activity(ActivityData::watching(format!(
-                    "{}/{} players",
-                    info.players.online, info.players.max
+                    "{}/{} players ({}ms)",
+                    info.players.online,
+                    info.players.max,
+                    latency.as_millis()
                 )))
                 .await
             {
@@ -0,0 +1,358 @@
+use std::{
+    io,
+    net::SocketAddr,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use tokio::{
+    net::{self, UdpSocket},
+    time,
+};
+
+pub const DEFAULT_PORT: u16 = 19132;
+
+// identifies offline (unconnected) RakNet messages
+const MAGIC: [u8; 16] = [
+    0x00, 0xff, 0xff, 0x00, 0xfe, 0xfe, 0xfe, 0xfe, 0xfd, 0xfd, 0xfd, 0xfd, 0x12, 0x34, 0x56, 0x78,
+];
+
+/*
+Field Name 	Field Type
+Packet ID 	Byte (0x01)
+Time 	Long
+Magic 	16 Bytes
+Client GUID 	Long
+*/
+pub struct UnconnectedPing {
+    time: i64,
+    client_guid: i64,
+}
+
+impl UnconnectedPing {
+    pub const PACKET_ID: u8 = 0x01;
+
+    pub fn new(time: i64, client_guid: i64) -> Self {
+        Self { time, client_guid }
+    }
+}
+
+impl From<UnconnectedPing> for Vec<u8> {
+    fn from(value: UnconnectedPing) -> Self {
+        let mut data = Vec::with_capacity(33);
+
+        data.push(UnconnectedPing::PACKET_ID);
+        data.extend(value.time.to_be_bytes());
+        data.extend(MAGIC);
+        data.extend(value.client_guid.to_be_bytes());
+
+        data
+    }
+}
+
+///////
+
+/*
+Field Name 	Field Type
+Packet ID 	Byte (0x1C)
+Time 	Long
+Server GUID 	Long
+Magic 	16 Bytes
+Server ID 	String (unsigned short length + utf8)
+*/
+pub struct UnconnectedPong {
+    pub time: i64,
+    pub server_guid: i64,
+    pub server_id: String,
+}
+
+impl UnconnectedPong {
+    pub const PACKET_ID: u8 = 0x1C;
+}
+
+impl TryFrom<&[u8]> for UnconnectedPong {
+    type Error = io::Error;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let too_short = || io::Error::new(io::ErrorKind::UnexpectedEof, "Pong packet is too short");
+
+        if value.first() != Some(&Self::PACKET_ID) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Expected unconnected pong packet",
+            ));
+        }
+
+        let header = value.get(1..35).ok_or_else(too_short)?;
+
+        let time = i64::from_be_bytes(header[0..8].try_into().unwrap());
+        let server_guid = i64::from_be_bytes(header[8..16].try_into().unwrap());
+
+        if header[16..32] != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Invalid RakNet magic",
+            ));
+        }
+
+        let length = u16::from_be_bytes([header[32], header[33]]) as usize;
+        let server_id = value.get(35..35 + length).ok_or_else(too_short)?;
+
+        Ok(Self {
+            time,
+            server_guid,
+            server_id: String::from_utf8_lossy(server_id).into_owned(),
+        })
+    }
+}
+
+///////
+
+#[derive(Debug)]
+pub struct BedrockStatus {
+    pub edition: String, // MCPE or MCEE for education edition
+    pub motd: String,
+    pub sub_motd: Option<String>,
+    pub protocol: i32,
+    pub version: String,
+    pub online: i32,
+    pub max: i32,
+    pub server_guid: i64,
+    pub gamemode: Option<String>,
+    pub gamemode_id: Option<i32>,
+    pub port_v4: Option<u16>,
+    pub port_v6: Option<u16>,
+}
+
+impl TryFrom<UnconnectedPong> for BedrockStatus {
+    type Error = io::Error;
+
+    /// Parses the semicolon separated server id,
+    /// e.g. `MCPE;Dedicated Server;390;1.14.60;0;10;13253860892328930865;Bedrock level;Survival;1;19132;19133;`
+    fn try_from(value: UnconnectedPong) -> Result<Self, Self::Error> {
+        log::debug!("bedrock server id: {}", value.server_id);
+
+        let fields: Vec<&str> = value.server_id.split(';').collect();
+
+        if fields.len() < 6 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Server id is missing fields: {}", value.server_id),
+            ));
+        }
+
+        let parse_number = |name: &str, field: &str| {
+            field.parse::<i32>().map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid {} \"{}\": {}", name, field, e),
+                )
+            })
+        };
+
+        let optional = |index: usize| {
+            fields
+                .get(index)
+                .filter(|f| !f.is_empty())
+                .map(|f| f.to_string())
+        };
+
+        Ok(Self {
+            edition: fields[0].to_string(),
+            motd: fields[1].to_string(),
+            protocol: parse_number("protocol", fields[2])?,
+            version: fields[3].to_string(),
+            online: parse_number("player count", fields[4])?,
+            max: parse_number("max player count", fields[5])?,
+            server_guid: value.server_guid,
+            sub_motd: optional(7),
+            gamemode: optional(8),
+            gamemode_id: optional(9).and_then(|f| f.parse().ok()),
+            port_v4: optional(10).and_then(|f| f.parse().ok()),
+            port_v6: optional(11).and_then(|f| f.parse().ok()),
+        })
+    }
+}
+
+///////
+
+pub struct BedrockClient {
+    host: String,
+    port: u16,
+}
+
+impl BedrockClient {
+    const ATTEMPTS: u32 = 3;
+    const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(2);
+
+    pub async fn new(host: String, port: u16) -> io::Result<Self> {
+        _ = Self::lookup(&host, port).await?;
+
+        Ok(Self { host, port })
+    }
+
+    async fn lookup(host: &str, port: u16) -> io::Result<SocketAddr> {
+        match net::lookup_host(format!("{}:{}", host, port)).await?.next() {
+            Some(addr) => Ok(addr),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "Hostname doesn't resolve to address",
+            )),
+        }
+    }
+
+    pub async fn status(&self) -> io::Result<BedrockStatus> {
+        let (status, _) = self.status_with_latency().await?;
+
+        Ok(status)
+    }
+
+    /// Sends an unconnected ping and measures the time until the pong arrives.
+    /// UDP datagrams may get lost, so the ping is retried a few times.
+    pub async fn status_with_latency(&self) -> io::Result<(BedrockStatus, Duration)> {
+        let addr = Self::lookup(&self.host, self.port).await?;
+
+        let bind_addr = match addr {
+            SocketAddr::V4(_) => "0.0.0.0:0",
+            SocketAddr::V6(_) => "[::]:0",
+        };
+        let socket = UdpSocket::bind(bind_addr).await?;
+        socket.connect(addr).await?;
+
+        let client_guid: i64 = rand::random();
+        let mut buf = [0; 1500];
+
+        for attempt in 1..=Self::ATTEMPTS {
+            let time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as i64)
+                .unwrap_or_default();
+
+            let start = Instant::now();
+
+            {
+                let packet = UnconnectedPing::new(time, client_guid);
+                let bytes: Vec<u8> = packet.into();
+
+                socket.send(bytes.as_slice()).await?;
+            }
+
+            // pongs to earlier attempts may still arrive, they don't use up the attempt
+            let deadline = time::Instant::now() + Self::ATTEMPT_TIMEOUT;
+            let answer = loop {
+                let n = match time::timeout_at(deadline, socket.recv(&mut buf)).await {
+                    Ok(res) => res?,
+                    Err(_) => break None,
+                };
+                let latency = start.elapsed();
+
+                let pong = match UnconnectedPong::try_from(&buf[0..n]) {
+                    Ok(pong) => pong,
+                    Err(e) => {
+                        log::debug!("ignoring malformed pong: {}", e);
+                        continue;
+                    }
+                };
+                if pong.time == time {
+                    break Some((pong, latency));
+                }
+
+                log::debug!("ignoring pong for another ping ({} != {})", pong.time, time);
+            };
+
+            let Some((pong, latency)) = answer else {
+                log::debug!("unconnected ping attempt {} timed out", attempt);
+                continue;
+            };
+
+            return Ok((BedrockStatus::try_from(pong)?, latency));
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "Server didn't answer the unconnected ping",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pong(time: i64, server_id: &str) -> Vec<u8> {
+        let mut data = vec![UnconnectedPong::PACKET_ID];
+        data.extend(time.to_be_bytes());
+        data.extend(42i64.to_be_bytes());
+        data.extend(MAGIC);
+        data.extend((server_id.len() as u16).to_be_bytes());
+        data.extend(server_id.as_bytes());
+        data
+    }
+
+    const SERVER_ID: &str =
+        "MCPE;Dedicated Server;390;1.14.60;0;10;13253860892328930865;Bedrock level;Survival;1;19132;19133;";
+
+    #[test]
+    fn parses_pong_and_server_id() {
+        let pong = UnconnectedPong::try_from(pong(7, SERVER_ID).as_slice()).unwrap();
+        assert_eq!((pong.time, pong.server_guid), (7, 42));
+
+        let status = BedrockStatus::try_from(pong).unwrap();
+        assert_eq!(status.edition, "MCPE");
+        assert_eq!(status.motd, "Dedicated Server");
+        assert_eq!(status.sub_motd.as_deref(), Some("Bedrock level"));
+        assert_eq!((status.protocol, status.version.as_str()), (390, "1.14.60"));
+        assert_eq!((status.online, status.max), (0, 10));
+        assert_eq!(status.gamemode.as_deref(), Some("Survival"));
+        assert_eq!(status.gamemode_id, Some(1));
+        assert_eq!((status.port_v4, status.port_v6), (Some(19132), Some(19133)));
+    }
+
+    #[test]
+    fn handles_short_and_missing_fields() {
+        // older servers stop after the player counts
+        let short =
+            UnconnectedPong::try_from(pong(0, "MCPE;Old;70;0.15.0;1;5").as_slice()).unwrap();
+        let status = BedrockStatus::try_from(short).unwrap();
+        assert_eq!((status.online, status.max), (1, 5));
+        assert_eq!(status.sub_motd, None);
+        assert_eq!(status.port_v4, None);
+
+        for server_id in ["MCPE;Missing;390;1.14.60;0", "MCPE;Bad;x;1.14.60;0;10"] {
+            let pong = UnconnectedPong::try_from(pong(0, server_id).as_slice()).unwrap();
+            assert!(BedrockStatus::try_from(pong).is_err());
+        }
+
+        let data = pong(0, SERVER_ID);
+        assert!(UnconnectedPong::try_from(&data[..20]).is_err());
+        assert!(UnconnectedPong::try_from(&data[..data.len() - 1]).is_err());
+        let mut bad_magic = data.clone();
+        bad_magic[18] = 0;
+        assert!(UnconnectedPong::try_from(bad_magic.as_slice()).is_err());
+    }
+
+    #[tokio::test]
+    async fn ignores_stale_and_malformed_pongs() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let port = server.local_addr().unwrap().port();
+
+        // answers only the first ping, after garbage and a pong meant for an older one
+        tokio::spawn(async move {
+            let mut buf = [0; 64];
+            let (_, peer) = server.recv_from(&mut buf).await.unwrap();
+            let time = i64::from_be_bytes(buf[1..9].try_into().unwrap());
+
+            server.send_to(b"\x1cgarbage", peer).await.unwrap();
+            server
+                .send_to(&pong(time - 1, "stale"), peer)
+                .await
+                .unwrap();
+            server.send_to(&pong(time, SERVER_ID), peer).await.unwrap();
+        });
+
+        let client = BedrockClient::new("127.0.0.1".to_string(), port)
+            .await
+            .unwrap();
+        let status = client.status().await.unwrap();
+        assert_eq!(status.motd, "Dedicated Server");
+    }
+}
@@ -1,102 +1,866 @@
-use std::io::{self, Cursor};
+use std::{
+    fmt,
+    future::Future,
+    io,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use tokio::{
-    io::AsyncWriteExt,
-    net::{self, TcpStream},
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time,
 };
+use tokio_socks::tcp::Socks5Stream;
+use uuid::Uuid;
 
 use crate::minecraft::{
-    packet::{handshake::Handshake, slp::SlpRequest},
+    connection::{self, Connection, Transport},
+    encryption,
+    limits::{LimitExceeded, Limits},
+    packet::{
+        codec::PacketCodec,
+        handshake::Handshake,
+        legacy::{LegacyPingRequest, LegacyPingResponse},
+        login::{EncryptionResponse, LoginPluginResponse, LoginStart},
+        ping::PingRequest,
+        slp::SlpRequest,
+        state::{
+            ClientboundRegistry, Configuration, Handshaking, LoginClientbound, Play, State, Status,
+            StatusClientbound,
+        },
+    },
+    session::{Account, SessionService},
+    srv::{self, SrvResolver, SrvTarget},
+    text_component::TextComponent,
     varint::VarInt,
 };
 
 use super::packet::slp::SlpResponse;
 
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum StatusMode {
+    /// Netty handshake and status request, 1.7+
+    #[default]
+    Modern,
+    /// 0xFE server list ping, Beta 1.8 - 1.6
+    Legacy,
+    /// Modern, falling back to legacy when the modern handshake fails
+    Auto,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisconnectReason {
+    Whitelisted,
+    Banned,
+    Outdated,
+    Full,
+    Other,
+}
+
+impl DisconnectReason {
+    /// Guesses the reason from the vanilla translation keys and common plugin messages.
+    fn classify(json: &str) -> Self {
+        let json = json.to_lowercase();
+        let contains_any = |patterns: &[&str]| patterns.iter().any(|p| json.contains(p));
+
+        if contains_any(&["not_whitelisted", "white-listed", "whitelist"]) {
+            Self::Whitelisted
+        } else if contains_any(&["disconnect.banned", "you are banned", "banned from"]) {
+            Self::Banned
+        } else if contains_any(&["outdated", "incompatible", "unsupported"]) {
+            Self::Outdated
+        } else if contains_any(&["server_full", "server is full"]) {
+            Self::Full
+        } else {
+            Self::Other
+        }
+    }
+}
+
+impl fmt::Display for DisconnectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Whitelisted => "whitelisted",
+            Self::Banned => "banned",
+            Self::Outdated => "outdated",
+            Self::Full => "full",
+            Self::Other => "refused",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Outcome of a login attempt, decided by the first packet the server sends.
+#[derive(Debug)]
+pub enum LoginProbe {
+    /// Encryption requested, players are authenticated with the session server
+    Online,
+    /// Compression or login success without authentication
+    Offline,
+    Disconnected {
+        reason: DisconnectReason,
+        message: Box<TextComponent>,
+    },
+}
+
+impl fmt::Display for LoginProbe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Online => write!(f, "online"),
+            Self::Offline => write!(f, "offline"),
+            Self::Disconnected { reason, .. } => write!(f, "{}", reason),
+        }
+    }
+}
+
+/// A connection after a successful login, the state depends on the protocol version.
+pub enum LoggedIn {
+    /// 1.20.2+
+    Configuration(Connection<Configuration>),
+    Play(Connection<Play>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Timeouts {
+    /// Maximum time of a single connection attempt.
+    pub connect: Duration,
+    /// Maximum time for reading all responses, after the connection is established.
+    pub read: Duration,
+    /// Maximum time of a whole request, including name resolution and every connection attempt.
+    pub overall: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            connect: Duration::from_secs(5),
+            read: Duration::from_secs(10),
+            overall: Duration::from_secs(15),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Socks5Proxy {
+    /// host:port of the proxy
+    pub addr: String,
+    pub credentials: Option<(String, String)>,
+}
+
+impl Socks5Proxy {
+    pub fn new<T: Into<String>>(addr: T) -> Self {
+        Self {
+            addr: addr.into(),
+            credentials: None,
+        }
+    }
+
+    pub fn credentials<T: Into<String>>(mut self, username: T, password: T) -> Self {
+        self.credentials = Some((username.into(), password.into()));
+        self
+    }
+
+    /// Connects to the target through the proxy, the target host is resolved by the proxy.
+    async fn connect(&self, host: &str, port: u16) -> io::Result<Socks5Stream<TcpStream>> {
+        let stream = TcpStream::connect(&self.addr).await?;
+
+        let res = match self.credentials {
+            Some((ref username, ref password)) => {
+                Socks5Stream::connect_with_password_and_socket(
+                    stream,
+                    (host, port),
+                    username,
+                    password,
+                )
+                .await
+            }
+            None => Socks5Stream::connect_with_socket(stream, (host, port)).await,
+        };
+
+        res.map_err(|e| match e {
+            tokio_socks::Error::Io(e) => e,
+            e => io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                format!("SOCKS5 proxy error: {}", e),
+            ),
+        })
+    }
+}
+
+pub struct ClientBuilder {
+    host: String,
+    port: u16,
+    protocol_version: VarInt,
+    virtual_host: Option<String>,
+    resolver: Option<SrvResolver>,
+    proxy: Option<Socks5Proxy>,
+    mode: StatusMode,
+    limits: Limits,
+    timeouts: Timeouts,
+}
+
+impl ClientBuilder {
+    pub fn new<T: Into<String>>(host: T) -> Self {
+        Self {
+            host: host.into(),
+            port: srv::DEFAULT_PORT,
+            protocol_version: Handshake::VERSION_UNSPECIFIED,
+            virtual_host: None,
+            resolver: None,
+            proxy: None,
+            mode: StatusMode::default(),
+            limits: Limits::default(),
+            timeouts: Timeouts::default(),
+        }
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Protocol version sent in the handshake, proxies often answer differently per version.
+    pub fn protocol_version(mut self, protocol_version: i32) -> Self {
+        self.protocol_version = VarInt(protocol_version);
+        self
+    }
+
+    /// Host sent in the handshake instead of the host connected to.
+    pub fn virtual_host<T: Into<String>>(mut self, virtual_host: T) -> Self {
+        self.virtual_host = Some(virtual_host.into());
+        self
+    }
+
+    /// Resolver used for SRV and address lookups, defaults to the system configuration.
+    pub fn resolver(mut self, resolver: SrvResolver) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    /// Routes every connection through a SOCKS5 proxy.
+    pub fn proxy(mut self, proxy: Socks5Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    pub fn mode(mut self, mode: StatusMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    pub async fn build(self) -> io::Result<Client> {
+        let resolver = match self.resolver {
+            Some(resolver) => resolver,
+            None => SrvResolver::from_system_conf()?,
+        };
+
+        // behind a proxy the addresses are resolved by the proxy
+        if self.proxy.is_none() {
+            _ = resolver.resolve(&self.host, self.port).await?;
+        }
+
+        Ok(Client {
+            virtual_host: self.virtual_host.unwrap_or_else(|| self.host.clone()),
+            host: self.host,
+            port: self.port,
+            protocol_version: self.protocol_version,
+            resolver,
+            proxy: self.proxy,
+            mode: self.mode,
+            limits: self.limits,
+            timeouts: self.timeouts,
+        })
+    }
+}
+
 pub struct Client {
     host: String,
     port: u16,
+    protocol_version: VarInt,
+    virtual_host: String,
+    resolver: SrvResolver,
+    proxy: Option<Socks5Proxy>,
+    mode: StatusMode,
+    limits: Limits,
+    timeouts: Timeouts,
 }
 
 impl Client {
     pub async fn new(host: String, port: u16) -> io::Result<Self> {
-        _ = net::lookup_host(format!("{}:{}", host, port)).await?;
+        Self::builder(host).port(port).build().await
+    }
 
-        Ok(Self { host, port })
+    pub fn builder<T: Into<String>>(host: T) -> ClientBuilder {
+        ClientBuilder::new(host)
     }
 
-    async fn connection(&self) -> io::Result<TcpStream> {
-        let addr = match net::lookup_host(format!("{}:{}", self.host, self.port))
-            .await?
-            .next()
-        {
-            Some(addr) => addr,
-            None => {
-                return Err(io::Error::new(
-                    io::ErrorKind::NotFound,
-                    "Hostname doesn't resolve to address",
-                ))
+    /// Connects to the server, also returns how long the tcp handshake took unless a proxy is used.
+    async fn connection(
+        &self,
+    ) -> io::Result<(Box<dyn Transport>, Option<SrvTarget>, Option<Duration>)> {
+        if let Some(ref proxy) = self.proxy {
+            let srv_target = self.resolver.srv_target(&self.host, self.port).await?;
+            let (host, port) = match srv_target {
+                Some(ref target) => (target.host.as_str(), target.port),
+                None => (self.host.as_str(), self.port),
+            };
+
+            let stream = match time::timeout(self.timeouts.connect, proxy.connect(host, port)).await
+            {
+                Ok(res) => res?,
+                Err(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!(
+                            "Connecting to {}:{} through the proxy timed out",
+                            host, port
+                        ),
+                    ))
+                }
+            };
+
+            return Ok((Box::new(stream), srv_target, None));
+        }
+
+        let resolved = self.resolver.resolve(&self.host, self.port).await?;
+
+        let start = Instant::now();
+        let stream = connection::connect(&resolved.addrs, self.timeouts.connect).await?;
+        let connect_time = start.elapsed();
+
+        Ok((Box::new(stream), resolved.srv_target, Some(connect_time)))
+    }
+
+    pub async fn status(&self) -> io::Result<SlpResponse> {
+        let (status, _) = self.status_with_latency().await?;
+
+        Ok(status)
+    }
+
+    /// Measures the round-trip time of a status-state ping/pong exchange.
+    /// Legacy servers have no ping packet, there the time until the kick packet arrives is used.
+    pub async fn ping(&self) -> io::Result<Duration> {
+        let (_, latency) = self.status_with_latency().await?;
+
+        Ok(latency)
+    }
+
+    /// Starts a login with a throwaway name to find out whether the server is in online mode
+    /// or refuses to let players join. Without a configured protocol version the version
+    /// reported by the server status is used.
+    pub async fn probe_login(&self) -> io::Result<LoginProbe> {
+        let probe = async {
+            let protocol_version = self.login_protocol_version().await?;
+
+            self.login_status(protocol_version).await
+        };
+
+        self.with_overall_timeout(probe).await
+    }
+
+    /// Like [`Self::probe_login`], using a protocol version already known from the status.
+    pub async fn probe_login_as(&self, protocol_version: i32) -> io::Result<LoginProbe> {
+        self.with_overall_timeout(self.login_status(VarInt(protocol_version)))
+            .await
+    }
+
+    /// Logs in with the account, authenticating with the session service if the server
+    /// is in online mode.
+    pub async fn login(
+        &self,
+        account: &Account,
+        session: &dyn SessionService,
+    ) -> io::Result<LoggedIn> {
+        let login = async {
+            let protocol_version = self.login_protocol_version().await?;
+
+            let (stream, _, _) = self.connection().await?;
+            let connection =
+                Connection::with_codec(stream, PacketCodec::new(self.limits.max_frame_length));
+
+            self.with_read_timeout(self.login_exchange_authenticated(
+                connection,
+                protocol_version,
+                account,
+                session,
+            ))
+            .await
+        };
+
+        self.with_overall_timeout(login).await
+    }
+
+    /// Fetches the server status and measures the latency on the same connection.
+    pub async fn status_with_latency(&self) -> io::Result<(SlpResponse, Duration)> {
+        let status = async {
+            match self.mode {
+                StatusMode::Modern => self.modern_status().await,
+                StatusMode::Legacy => self.legacy_status().await,
+                StatusMode::Auto => match self.modern_status().await {
+                    Ok(res) => Ok(res),
+                    Err(e) => match e.kind() {
+                        io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused => Err(e),
+                        _ => {
+                            log::debug!("modern status failed ({}), trying legacy ping", e);
+
+                            self.legacy_status().await
+                        }
+                    },
+                },
             }
         };
 
-        let stream =
-            std::net::TcpStream::connect_timeout(&addr, std::time::Duration::from_secs(5))?;
-        stream.set_nonblocking(true)?;
+        self.with_overall_timeout(status).await
+    }
 
-        Ok(TcpStream::from_std(stream)?)
+    async fn with_overall_timeout<T>(
+        &self,
+        future: impl Future<Output = io::Result<T>>,
+    ) -> io::Result<T> {
+        match time::timeout(self.timeouts.overall, future).await {
+            Ok(res) => res,
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("Request took longer than {:?}", self.timeouts.overall),
+            )),
+        }
     }
 
-    pub async fn status(&self) -> io::Result<SlpResponse> {
-        let mut stream = self.connection().await?;
+    async fn with_read_timeout<T>(
+        &self,
+        future: impl Future<Output = io::Result<T>>,
+    ) -> io::Result<T> {
+        match time::timeout(self.timeouts.read, future).await {
+            Ok(res) => res,
+            Err(_) => Err(LimitExceeded::ReadTimeout(self.timeouts.read).into()),
+        }
+    }
+
+    async fn legacy_status(&self) -> io::Result<(SlpResponse, Duration)> {
+        let (mut stream, srv_target, connect_time) = self.connection().await?;
+
+        let (mut status, latency) = self
+            .with_read_timeout(self.legacy_exchange(&mut stream))
+            .await?;
+        status.srv_target = srv_target;
+        status.connect_time = connect_time;
+
+        Ok((status, latency))
+    }
+
+    async fn legacy_exchange(
+        &self,
+        stream: &mut Box<dyn Transport>,
+    ) -> io::Result<(SlpResponse, Duration)> {
+        let start = Instant::now();
 
         {
-            let packet = Handshake::new(
-                Handshake::VERSION_UNSPECIFIED,
-                self.host.clone(),
+            let packet = LegacyPingRequest::new(
+                LegacyPingRequest::VERSION_1_6_4,
+                self.virtual_host.clone(),
                 self.port,
-                Handshake::NEXT_STATE_STATUS,
             );
             let bytes: Vec<u8> = packet.into();
 
             stream.write_all(bytes.as_slice()).await?;
         }
 
-        {
-            let packet = SlpRequest;
-            let bytes: Vec<u8> = packet.into();
+        let mut data = vec![0; 3]; // packet id + string length
+        stream.read_exact(&mut data).await?;
+        let latency = start.elapsed();
 
-            stream.write_all(bytes.as_slice()).await?;
+        let length = u16::from_be_bytes([data[1], data[2]]) as usize;
+        data.resize(3 + length * 2, 0);
+        stream.read_exact(&mut data[3..]).await?;
+
+        Ok((LegacyPingResponse::try_from(data)?.into(), latency))
+    }
+
+    async fn modern_status(&self) -> io::Result<(SlpResponse, Duration)> {
+        let (stream, srv_target, connect_time) = self.connection().await?;
+        let connection =
+            Connection::with_codec(stream, PacketCodec::new(self.limits.max_frame_length));
+
+        let (mut status, latency) = self
+            .with_read_timeout(self.modern_exchange(connection))
+            .await?;
+        status.srv_target = srv_target;
+        status.connect_time = connect_time;
+
+        Ok((status, latency))
+    }
+
+    async fn modern_exchange(
+        &self,
+        connection: Connection<Handshaking>,
+    ) -> io::Result<(SlpResponse, Duration)> {
+        let mut connection = connection
+            .into_status(self.protocol_version, self.virtual_host.clone(), self.port)
+            .await?;
+
+        let status = self.request_status(&mut connection).await?;
+
+        // the notchian client uses the current time as payload, the server has to echo it back
+        let payload = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or_default();
+
+        let start = Instant::now();
+
+        connection.send(PingRequest::new(payload)).await?;
+        let pong = match connection.recv().await? {
+            StatusClientbound::Pong(pong) => pong,
+            packet => return Err(unexpected_packet(&connection, packet.id())),
+        };
+
+        let latency = start.elapsed();
+
+        if pong.payload != payload {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Pong payload mismatch: expected {}, got {}",
+                    payload, pong.payload
+                ),
+            ));
         }
 
-        stream.readable().await?;
+        log::debug!("latency: {:?}", latency);
 
-        let mut data = Vec::new();
+        Ok((status, latency))
+    }
 
-        let packet_size: usize;
-        {
-            let mut buf = [0; 5]; // the varint packet len has a maximum of 5 bytes
-            stream.try_read(&mut buf)?;
+    async fn request_status(&self, connection: &mut Connection<Status>) -> io::Result<SlpResponse> {
+        connection.send(SlpRequest).await?;
+
+        let response = match connection.recv().await? {
+            StatusClientbound::Response(response) => response,
+            packet => return Err(unexpected_packet(connection, packet.id())),
+        };
+
+        log::debug!("slp response: {}", response.json);
+
+        SlpResponse::from_json(&response.json, &self.limits)
+    }
 
-            let packet_len = VarInt::from_bytes(Cursor::new(&buf))?;
-            data.extend_from_slice(&buf);
+    // servers disconnect clients announcing a different version than their own
+    async fn login_protocol_version(&self) -> io::Result<VarInt> {
+        match self.protocol_version == Handshake::VERSION_UNSPECIFIED {
+            true => {
+                let (status, _) = self.modern_status().await?;
+                Ok(VarInt(status.version.protocol))
+            }
+            false => Ok(self.protocol_version),
+        }
+    }
+
+    async fn login_status(&self, protocol_version: VarInt) -> io::Result<LoginProbe> {
+        let (stream, _, _) = self.connection().await?;
+        let connection =
+            Connection::with_codec(stream, PacketCodec::new(self.limits.max_frame_length));
+
+        self.with_read_timeout(self.login_exchange(connection, protocol_version))
+            .await
+    }
 
-            packet_size = packet_len.0 as usize + packet_len.to_bytes().len();
-            log::debug!("packet size: {}", packet_size);
+    async fn login_exchange(
+        &self,
+        connection: Connection<Handshaking>,
+        protocol_version: VarInt,
+    ) -> io::Result<LoginProbe> {
+        let mut connection = connection
+            .into_login(protocol_version, self.virtual_host.clone(), self.port)
+            .await?;
+
+        // names are limited to 16 alphanumeric characters
+        let name = format!("Helferbiene{:04}", rand::random::<u16>() % 10000);
+        connection
+            .send(LoginStart::new(
+                protocol_version,
+                name,
+                Uuid::from_u128(rand::random()),
+            ))
+            .await?;
+
+        loop {
+            match connection.recv().await? {
+                LoginClientbound::Disconnect(disconnect) => {
+                    return Ok(LoginProbe::Disconnected {
+                        reason: DisconnectReason::classify(&disconnect.json),
+                        message: Box::new(disconnect.reason()),
+                    });
+                }
+                LoginClientbound::EncryptionRequest(request) => {
+                    return match request.should_authenticate {
+                        true => Ok(LoginProbe::Online),
+                        false => Ok(LoginProbe::Offline),
+                    };
+                }
+                LoginClientbound::SetCompression(_) | LoginClientbound::Success(_) => {
+                    return Ok(LoginProbe::Offline)
+                }
+                LoginClientbound::PluginRequest(request) => {
+                    connection
+                        .send(LoginPluginResponse::not_understood(request.message_id))
+                        .await?;
+                }
+            }
         }
+    }
+
+    async fn login_exchange_authenticated(
+        &self,
+        connection: Connection<Handshaking>,
+        protocol_version: VarInt,
+        account: &Account,
+        session: &dyn SessionService,
+    ) -> io::Result<LoggedIn> {
+        let mut connection = connection
+            .into_login(protocol_version, self.virtual_host.clone(), self.port)
+            .await?;
+
+        connection
+            .send(LoginStart::new(
+                protocol_version,
+                account.name.clone(),
+                account.uuid,
+            ))
+            .await?;
 
-        let mut buf = [0; 1024];
-        while data.len() < packet_size - 1 {
-            stream.readable().await?;
+        loop {
+            match connection.recv().await? {
+                LoginClientbound::Disconnect(disconnect) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        format!(
+                            "Disconnected during login: {}",
+                            disconnect.reason().format_string()
+                        ),
+                    ));
+                }
+                LoginClientbound::EncryptionRequest(request) => {
+                    let shared_secret = encryption::generate_shared_secret();
 
-            match stream.try_read(&mut buf) {
-                Ok(0) => break,
-                Ok(n) => data.extend_from_slice(&buf[0..n]),
-                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => continue,
-                Err(e) => return Err(e.into()),
+                    if request.should_authenticate {
+                        let hash = encryption::server_hash(
+                            &request.server_id,
+                            &shared_secret,
+                            &request.public_key,
+                        );
+
+                        session.join(account, &hash).await?;
+                    }
+
+                    connection
+                        .send(EncryptionResponse::new(
+                            protocol_version,
+                            encryption::encrypt_with_public_key(
+                                &request.public_key,
+                                &shared_secret,
+                            )?,
+                            encryption::encrypt_with_public_key(
+                                &request.public_key,
+                                &request.verify_token,
+                            )?,
+                        ))
+                        .await?;
+
+                    connection.enable_encryption(&shared_secret);
+                }
+                LoginClientbound::SetCompression(compression) => {
+                    connection.set_compression_threshold(compression.threshold.0);
+                }
+                LoginClientbound::PluginRequest(request) => {
+                    connection
+                        .send(LoginPluginResponse::not_understood(request.message_id))
+                        .await?;
+                }
+                LoginClientbound::Success(_) => {
+                    return match protocol_version.0 >= LoginStart::VERSION_1_20_2 {
+                        true => Ok(LoggedIn::Configuration(
+                            connection.into_configuration().await?,
+                        )),
+                        false => Ok(LoggedIn::Play(connection.into_play())),
+                    };
+                }
             }
+        }
+    }
+}
+
+fn unexpected_packet<S: State>(connection: &Connection<S>, id: VarInt) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "Unexpected packet id {:#04x} in the {} state",
+            id.0,
+            connection.state()
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use futures::{SinkExt, StreamExt};
+    use rsa::{pkcs8::EncodePublicKey, Pkcs1v15Encrypt, RsaPrivateKey};
+    use serenity::async_trait;
+    use tokio_util::codec::Framed;
+
+    use super::*;
+    use crate::minecraft::{
+        encryption::SharedSecret,
+        packet::{login::LoginAcknowledged, Packet},
+    };
 
-            log::debug!("read: {}/{}", data.len(), packet_size);
+    #[test]
+    fn classifies_vanilla_disconnects() {
+        let cases = [
+            (
+                r#"{"translate":"multiplayer.disconnect.not_whitelisted"}"#,
+                DisconnectReason::Whitelisted,
+            ),
+            (
+                r#"{"text":"You are not white-listed on this server!"}"#,
+                DisconnectReason::Whitelisted,
+            ),
+            (
+                r#"{"translate":"multiplayer.disconnect.banned.reason","with":["Griefing"]}"#,
+                DisconnectReason::Banned,
+            ),
+            (
+                r#"{"translate":"multiplayer.disconnect.banned_ip.reason","with":["Spam"]}"#,
+                DisconnectReason::Banned,
+            ),
+            (
+                r#"{"translate":"multiplayer.disconnect.outdated_client","with":["1.20.4"]}"#,
+                DisconnectReason::Outdated,
+            ),
+            (
+                r#"{"translate":"multiplayer.disconnect.incompatible","with":["1.20.4"]}"#,
+                DisconnectReason::Outdated,
+            ),
+            (
+                r#"{"text":"Outdated server! I'm still on 1.8.8"}"#,
+                DisconnectReason::Outdated,
+            ),
+            (
+                r#"{"translate":"multiplayer.disconnect.server_full"}"#,
+                DisconnectReason::Full,
+            ),
+            (r#"{"text":"The server is full!"}"#, DisconnectReason::Full),
+            (
+                r#"{"translate":"multiplayer.disconnect.server_shutdown"}"#,
+                DisconnectReason::Other,
+            ),
+        ];
+
+        for (json, reason) in cases {
+            assert_eq!(DisconnectReason::classify(json), reason, "{}", json);
+        }
+    }
+
+    /// Records the server hash instead of calling the session server.
+    #[derive(Default)]
+    struct LocalSession {
+        server_hash: Mutex<Option<String>>,
+    }
+
+    #[async_trait]
+    impl SessionService for LocalSession {
+        async fn join(&self, _: &Account, server_hash: &str) -> io::Result<()> {
+            *self.server_hash.lock().unwrap() = Some(server_hash.to_string());
+            Ok(())
         }
+    }
+
+    fn byte_array(data: &[u8]) -> Vec<u8> {
+        [VarInt(data.len() as i32).to_bytes(), data.to_vec()].concat()
+    }
+
+    fn read_byte_array(data: &[u8]) -> (Vec<u8>, &[u8]) {
+        let (length, size) = VarInt::from_partial(data).unwrap().unwrap();
+        let end = size + length.0 as usize;
+        (data[size..end].to_vec(), &data[end..])
+    }
+
+    #[tokio::test]
+    async fn login_with_encryption_and_compression() {
+        let client = Client::builder("127.0.0.1")
+            .protocol_version(765)
+            .build()
+            .await
+            .unwrap();
+        let account = Account {
+            name: "Helferbiene".into(),
+            uuid: Uuid::from_u128(0x0123456789abcdef0123456789abcdef),
+            access_token: "token".into(),
+        };
+        let session = LocalSession::default();
+
+        let (client_io, server_io) = tokio::io::duplex(4096);
+        let connection = Connection::new(Box::new(client_io));
+
+        let server = async {
+            let mut server = Framed::new(server_io, PacketCodec::default());
+            let key = RsaPrivateKey::new(&mut rand::thread_rng(), 1024).unwrap();
+            let public_key = key.to_public_key().to_public_key_der().unwrap().into_vec();
+
+            let handshake = server.next().await.unwrap().unwrap();
+            assert_eq!(handshake.data.last(), Some(&2));
+            let login_start = server.next().await.unwrap().unwrap();
+            assert!(login_start.data.ends_with(account.uuid.as_bytes()));
+
+            let request = [
+                vec![0x01],
+                byte_array(b""),
+                byte_array(&public_key),
+                byte_array(b"tokn"),
+            ]
+            .concat();
+            server.send(request).await.unwrap();
+
+            let response = server.next().await.unwrap().unwrap();
+            assert_eq!(response.id, EncryptionResponse::PACKET_ID);
+            let (secret, rest) = read_byte_array(&response.data);
+            let (token, _) = read_byte_array(rest);
+            let secret = key.decrypt(Pkcs1v15Encrypt, &secret).unwrap();
+            assert_eq!(key.decrypt(Pkcs1v15Encrypt, &token).unwrap(), b"tokn");
+
+            let secret: SharedSecret = secret.try_into().unwrap();
+            server.codec_mut().enable_encryption(&secret);
+            server.send(vec![0x03, 0x40]).await.unwrap();
+            server.codec_mut().set_compression_threshold(0x40);
+            server.send(vec![0x02; 100]).await.unwrap();
+
+            let acknowledged = server.next().await.unwrap().unwrap();
+            assert_eq!(acknowledged.id, LoginAcknowledged::PACKET_ID);
+
+            encryption::server_hash("", &secret, &public_key)
+        };
+
+        let (expected_hash, res) = tokio::join!(
+            server,
+            client.login_exchange_authenticated(connection, VarInt(765), &account, &session)
+        );
 
-        SlpResponse::try_from(data)
+        assert!(matches!(res.unwrap(), LoggedIn::Configuration(_)));
+        assert_eq!(
+            session.server_hash.into_inner().unwrap(),
+            Some(expected_hash)
+        );
     }
 }
@@ -0,0 +1,212 @@
+use std::{io, marker::PhantomData, net::SocketAddr, time::Duration};
+
+use futures::{SinkExt, StreamExt};
+use itertools::Itertools;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+    task::JoinSet,
+    time,
+};
+use tokio_util::codec::Framed;
+
+use super::{
+    encryption::SharedSecret,
+    packet::{
+        codec::{PacketCodec, RawPacket},
+        handshake::Handshake,
+        login::LoginAcknowledged,
+        state::{
+            ClientboundRegistry, Configuration, ConnectionState, Handshaking, Login, Play,
+            ServerboundRegistry, State, Status,
+        },
+    },
+    varint::VarInt,
+};
+
+/// Any byte stream a connection can run over, e.g. a plain or proxied tcp stream.
+pub trait Transport: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Transport for T {}
+
+/// A connection speaking length prefixed packets, typed by its protocol state.
+/// Only the packets registered for the state can be sent and received,
+/// the state changes by consuming the connection.
+pub struct Connection<S: State = Handshaking> {
+    framed: Framed<Box<dyn Transport>, PacketCodec>,
+    state: PhantomData<S>,
+}
+
+impl Connection<Handshaking> {
+    pub fn new(stream: Box<dyn Transport>) -> Self {
+        Self::with_codec(stream, PacketCodec::default())
+    }
+
+    pub fn with_codec(stream: Box<dyn Transport>, codec: PacketCodec) -> Self {
+        Self {
+            framed: Framed::new(stream, codec),
+            state: PhantomData,
+        }
+    }
+
+    pub async fn into_status(
+        self,
+        protocol_version: VarInt,
+        host: String,
+        port: u16,
+    ) -> io::Result<Connection<Status>> {
+        self.handshake(protocol_version, host, port, Handshake::NEXT_STATE_STATUS)
+            .await
+    }
+
+    pub async fn into_login(
+        self,
+        protocol_version: VarInt,
+        host: String,
+        port: u16,
+    ) -> io::Result<Connection<Login>> {
+        self.handshake(protocol_version, host, port, Handshake::NEXT_STATE_LOGIN)
+            .await
+    }
+
+    async fn handshake<T: State>(
+        mut self,
+        protocol_version: VarInt,
+        host: String,
+        port: u16,
+        next_state: VarInt,
+    ) -> io::Result<Connection<T>> {
+        self.send(Handshake::new(protocol_version, host, port, next_state))
+            .await?;
+
+        Ok(self.transition())
+    }
+}
+
+impl Connection<Login> {
+    /// Applies the threshold of a Set Compression packet to every following frame.
+    pub fn set_compression_threshold(&mut self, threshold: i32) {
+        self.framed.codec_mut().set_compression_threshold(threshold);
+    }
+
+    /// Encrypts the connection with the shared secret sent in the Encryption Response.
+    pub fn enable_encryption(&mut self, shared_secret: &SharedSecret) {
+        self.framed.codec_mut().enable_encryption(shared_secret);
+    }
+
+    /// Acknowledges the Login Success, 1.20.2+
+    pub async fn into_configuration(mut self) -> io::Result<Connection<Configuration>> {
+        self.send(LoginAcknowledged).await?;
+
+        Ok(self.transition())
+    }
+
+    /// Before 1.20.2 the Login Success switches straight to play.
+    pub fn into_play(self) -> Connection<Play> {
+        self.transition()
+    }
+}
+
+impl<S: State> Connection<S> {
+    pub fn state(&self) -> ConnectionState {
+        S::STATE
+    }
+
+    pub async fn send<P>(&mut self, packet: P) -> io::Result<()>
+    where
+        S::Serverbound: From<P>,
+    {
+        let packet: S::Serverbound = packet.into();
+
+        self.framed.send(packet.encode_packet()?).await
+    }
+
+    /// Receives the next frame without looking at the packet id.
+    pub async fn recv_raw(&mut self) -> io::Result<RawPacket> {
+        match self.framed.next().await {
+            Some(packet) => packet,
+            None => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Connection closed by server",
+            )),
+        }
+    }
+
+    /// Receives the next packet, failing with [`UnknownPacket`](super::packet::state::UnknownPacket)
+    /// if its id isn't registered for the state.
+    pub async fn recv(&mut self) -> io::Result<S::Clientbound> {
+        S::Clientbound::decode(&self.recv_raw().await?)
+    }
+
+    fn transition<T: State>(self) -> Connection<T> {
+        log::debug!("connection state: {} -> {}", S::STATE, T::STATE);
+
+        Connection {
+            framed: self.framed,
+            state: PhantomData,
+        }
+    }
+}
+
+// time to wait for an attempt before starting the next one in parallel (RFC 8305)
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Connects to the first address accepting the connection, happy eyeballs style.
+/// The address families are interleaved and a new attempt is started whenever
+/// the previous one failed or didn't succeed within 250ms.
+pub async fn connect(addrs: &[SocketAddr], timeout: Duration) -> io::Result<TcpStream> {
+    let (v6, v4): (Vec<SocketAddr>, Vec<SocketAddr>) = addrs.iter().partition(|a| a.is_ipv6());
+    let mut pending = v6.into_iter().interleave(v4);
+
+    let mut attempts = JoinSet::new();
+    let mut last_error = None;
+
+    loop {
+        let has_pending = match pending.next() {
+            Some(addr) => {
+                attempts.spawn(async move {
+                    match time::timeout(timeout, TcpStream::connect(addr)).await {
+                        Ok(res) => res.map_err(|e| {
+                            io::Error::new(
+                                e.kind(),
+                                format!("Connecting to {} failed: {}", addr, e),
+                            )
+                        }),
+                        Err(_) => Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            format!("Connecting to {} timed out", addr),
+                        )),
+                    }
+                });
+                true
+            }
+            None => false,
+        };
+
+        let finished = match has_pending {
+            true => match time::timeout(CONNECTION_ATTEMPT_DELAY, attempts.join_next()).await {
+                Ok(finished) => finished,
+                Err(_) => continue,
+            },
+            false => attempts.join_next().await,
+        };
+
+        // the remaining attempts are aborted when the join set is dropped
+        match finished {
+            Some(Ok(Ok(stream))) => return Ok(stream),
+            Some(Ok(Err(e))) => {
+                log::debug!("{}", e);
+                last_error = Some(e);
+            }
+            Some(Err(e)) => last_error = Some(io::Error::other(e)),
+            None => break,
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "Hostname doesn't resolve to address",
+        )
+    }))
+}
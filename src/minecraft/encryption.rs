@@ -0,0 +1,110 @@
+use std::io;
+
+use aes::{
+    cipher::{
+        generic_array::typenum::U1, inout::InOutBuf, BlockDecryptMut, BlockEncryptMut, KeyIvInit,
+    },
+    Aes128,
+};
+use num_bigint::BigInt;
+use rsa::{pkcs8::DecodePublicKey, Pkcs1v15Encrypt, RsaPublicKey};
+use sha1::{Digest, Sha1};
+
+pub type SharedSecret = [u8; 16];
+
+/// AES-128-CFB8 for both directions, the shared secret is used as key and iv.
+pub struct PacketCipher {
+    encryptor: cfb8::Encryptor<Aes128>,
+    decryptor: cfb8::Decryptor<Aes128>,
+}
+
+impl PacketCipher {
+    pub fn new(shared_secret: &SharedSecret) -> Self {
+        Self {
+            encryptor: cfb8::Encryptor::new(shared_secret.into(), shared_secret.into()),
+            decryptor: cfb8::Decryptor::new(shared_secret.into(), shared_secret.into()),
+        }
+    }
+
+    pub fn encrypt(&mut self, buf: &mut [u8]) {
+        let (blocks, _) = InOutBuf::from(buf).into_chunks::<U1>();
+        self.encryptor.encrypt_blocks_inout_mut(blocks);
+    }
+
+    pub fn decrypt(&mut self, buf: &mut [u8]) {
+        let (blocks, _) = InOutBuf::from(buf).into_chunks::<U1>();
+        self.decryptor.decrypt_blocks_inout_mut(blocks);
+    }
+}
+
+pub fn generate_shared_secret() -> SharedSecret {
+    rand::random()
+}
+
+/// Encrypts the shared secret or verify token with the DER encoded public key of the server.
+pub fn encrypt_with_public_key(public_key: &[u8], data: &[u8]) -> io::Result<Vec<u8>> {
+    let key = RsaPublicKey::from_public_key_der(public_key).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid server public key: {}", e),
+        )
+    })?;
+
+    key.encrypt(&mut rand::thread_rng(), Pkcs1v15Encrypt, data)
+        .map_err(io::Error::other)
+}
+
+/// The server id sent to the session server, a sha1 digest printed as signed
+/// two's complement hex number without leading zeros.
+pub fn server_hash(server_id: &str, shared_secret: &[u8], public_key: &[u8]) -> String {
+    let digest = Sha1::new()
+        .chain_update(server_id.as_bytes())
+        .chain_update(shared_secret)
+        .chain_update(public_key)
+        .finalize();
+
+    BigInt::from_signed_bytes_be(&digest).to_str_radix(16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name_hash(name: &str) -> String {
+        server_hash(name, &[], &[])
+    }
+
+    #[test]
+    fn server_hash_is_signed_hex() {
+        assert_eq!(
+            name_hash("Notch"),
+            "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48"
+        );
+        assert_eq!(
+            name_hash("jeb_"),
+            "-7c9d5b0044c130109a5d7b5fb5c317c02b4e28c1"
+        );
+        assert_eq!(
+            name_hash("simon"),
+            "88e16a1019277b15d58faf0541e11910eb756f6"
+        );
+    }
+
+    #[test]
+    fn cipher_round_trips_in_pieces() {
+        let secret = generate_shared_secret();
+        let mut sender = PacketCipher::new(&secret);
+        let mut receiver = PacketCipher::new(&secret);
+
+        let plain: Vec<u8> = (0..=255).collect();
+        let mut data = plain.clone();
+        sender.encrypt(&mut data);
+        assert_ne!(data, plain);
+
+        // the stream state carries over between calls
+        let (first, second) = data.split_at_mut(100);
+        receiver.decrypt(first);
+        receiver.decrypt(second);
+        assert_eq!(data, plain);
+    }
+}
@@ -0,0 +1,471 @@
+use std::{fmt, time::Duration};
+
+use crate::minecraft::{
+    packet::{legacy::LegacyPingResponse, slp::SlpResponse},
+    protocol_version,
+    query::QueryFullStat,
+};
+
+/// Server software that leaves a recognizable trace in its responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Software {
+    Vanilla,
+    CraftBukkit,
+    Spigot,
+    Paper,
+    Purpur,
+    Pufferfish,
+    Folia,
+    Forge,
+    NeoForge,
+    BungeeCord,
+    Waterfall,
+    Velocity,
+}
+
+// forks come before the software they're based on, their names often contain both
+const NAMES: [(&str, Software); 11] = [
+    ("purpur", Software::Purpur),
+    ("pufferfish", Software::Pufferfish),
+    ("folia", Software::Folia),
+    ("paper", Software::Paper),
+    ("spigot", Software::Spigot),
+    ("craftbukkit", Software::CraftBukkit),
+    ("waterfall", Software::Waterfall),
+    ("bungeecord", Software::BungeeCord),
+    ("velocity", Software::Velocity),
+    ("neoforge", Software::NeoForge),
+    ("forge", Software::Forge),
+];
+
+// the oldest clients the via plugins let join
+const VIA_BACKWARDS_OLDEST: &str = "1.10";
+const VIA_REWIND_OLDEST: &str = "1.7";
+
+// how much longer than the tcp handshake the ping may take before a relay is suspected,
+// a direct server answers both within about one round trip
+const RELAY_DELAY: Duration = Duration::from_millis(20);
+
+impl Software {
+    /// Finds a known software name anywhere in the text, ignoring case.
+    pub fn from_name(text: &str) -> Option<Self> {
+        let text = text.to_lowercase();
+
+        NAMES
+            .iter()
+            .find(|(name, _)| text.contains(name))
+            .map(|(_, software)| *software)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Vanilla => "Vanilla",
+            Self::CraftBukkit => "CraftBukkit",
+            Self::Spigot => "Spigot",
+            Self::Paper => "Paper",
+            Self::Purpur => "Purpur",
+            Self::Pufferfish => "Pufferfish",
+            Self::Folia => "Folia",
+            Self::Forge => "Forge",
+            Self::NeoForge => "NeoForge",
+            Self::BungeeCord => "BungeeCord",
+            Self::Waterfall => "Waterfall",
+            Self::Velocity => "Velocity",
+        }
+    }
+
+    pub fn is_proxy(&self) -> bool {
+        matches!(self, Self::BungeeCord | Self::Waterfall | Self::Velocity)
+    }
+}
+
+impl fmt::Display for Software {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A best guess of what runs behind an address, pieced together from the responses.
+#[derive(Debug, Clone, Default)]
+pub struct ServerFingerprint {
+    pub software: Option<Software>,
+    pub proxy: bool,
+    /// Geyser lets bedrock players join.
+    pub geyser: bool,
+    /// The client versions that can join, e.g. "1.8–1.20".
+    pub versions: Option<String>,
+    /// What the guess is based on.
+    pub hints: Vec<String>,
+}
+
+impl ServerFingerprint {
+    /// Analyses a status response and the latency measured with it,
+    /// the query and legacy responses are optional extra evidence.
+    pub fn analyze(
+        status: &SlpResponse,
+        latency: Duration,
+        query: Option<&QueryFullStat>,
+        legacy: Option<&LegacyPingResponse>,
+    ) -> Self {
+        let mut fingerprint = Self::default();
+
+        let mut names = vec![("version name", status.version.name.as_str())];
+        if let Some(query) = query {
+            if let Some(ref software) = query.plugins.server_software {
+                names.push(("query", software.as_str()));
+            }
+            names.push(("query", query.version.as_str()));
+        }
+        if let Some(version) = legacy.and_then(|l| l.version.as_deref()) {
+            names.push(("legacy ping", version));
+        }
+
+        // a proxy name wins, the proxy might forward the status of a backend
+        let mut named = names
+            .iter()
+            .filter_map(|(source, name)| Software::from_name(name).map(|s| (*source, s)));
+        let named = match named.clone().find(|(_, software)| software.is_proxy()) {
+            Some(proxy) => Some(proxy),
+            None => named.next(),
+        };
+        if let Some((source, software)) = named {
+            fingerprint.software = Some(software);
+            fingerprint
+                .hints
+                .push(format!("{} in the {}", software, source));
+        }
+
+        if let Some(ref forge_data) = status.forge_data {
+            let software = match forge_data.mods.iter().any(|m| m.mod_id == "neoforge") {
+                true => Software::NeoForge,
+                false => Software::Forge,
+            };
+            fingerprint.software.get_or_insert(software);
+            fingerprint
+                .hints
+                .push(format!("forgeData with {} mods", forge_data.mods.len()));
+        } else if status.modinfo.as_ref().is_some_and(|m| m.type_ == "FML") {
+            fingerprint.software.get_or_insert(Software::Forge);
+            fingerprint.hints.push("FML modinfo".to_string());
+        }
+
+        // bungeecord answers clients it doesn't support with the range it does
+        if status.version.name.starts_with("Requires MC") {
+            fingerprint.software.get_or_insert(Software::BungeeCord);
+            fingerprint
+                .hints
+                .push("unsupported version message".to_string());
+        }
+
+        // vanilla reports nothing but the version number
+        let plain_version = !status.version.name.is_empty()
+            && status
+                .version
+                .name
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == '.');
+        if fingerprint.software.is_none() && plain_version {
+            fingerprint.software = Some(Software::Vanilla);
+            fingerprint.hints.push("plain version name".to_string());
+        }
+
+        // a mod, vanilla servers don't know this field
+        if status.prevents_chat_reports == Some(true) {
+            fingerprint.hints.push("No Chat Reports mod".to_string());
+        }
+
+        fingerprint.proxy = fingerprint.software.is_some_and(|s| s.is_proxy());
+
+        let plugins: &[String] = query.map_or(&[], |q| &q.plugins.plugins);
+        let has_plugin = |name: &str| {
+            let name = name.to_lowercase();
+            plugins.iter().any(|p| p.to_lowercase().contains(&name))
+        };
+
+        fingerprint.geyser = has_plugin("geyser")
+            || names
+                .iter()
+                .any(|(_, name)| name.to_lowercase().contains("geyser"));
+        if fingerprint.geyser {
+            fingerprint.hints.push("Geyser".to_string());
+        }
+
+        fingerprint.versions = Self::versions(status, legacy, &has_plugin);
+        if has_plugin("viaversion") {
+            fingerprint.hints.push("ViaVersion".to_string());
+        }
+
+        // tcp proxies and ddos protection edges accept the connection themselves and relay the
+        // ping to a backend elsewhere, which adds that round trip to the ping only
+        if let Some(connect_time) = status.connect_time {
+            if latency > connect_time * 2 && latency - connect_time > RELAY_DELAY {
+                fingerprint.hints.push(format!(
+                    "ping {}ms slower than connecting, likely relayed",
+                    (latency - connect_time).as_millis()
+                ));
+            }
+        }
+
+        fingerprint
+    }
+
+    fn versions(
+        status: &SlpResponse,
+        legacy: Option<&LegacyPingResponse>,
+        has_plugin: &impl Fn(&str) -> bool,
+    ) -> Option<String> {
+        // servers older than 1.4 send neither a version nor a protocol
+        if legacy.is_some_and(|l| l.protocol.is_none()) {
+            return Some("Beta 1.8–1.3".to_string());
+        }
+
+        // proxies name their range, e.g. "BungeeCord 1.8.x-1.20.x" or "Requires MC 1.8 / 1.20"
+        let numbers = protocol_version::version_numbers(&status.version.name);
+        if let [first, .., last] = numbers.as_slice() {
+            return Some(format!("{}–{}", first, last));
+        }
+
+        let protocol = legacy
+            .and_then(|l| l.protocol)
+            .unwrap_or(status.version.protocol);
+        // a single number in the name is more precise than the protocol, e.g. "Paper 1.20.4"
+        let versions = protocol_version::lookup(protocol);
+        let (first, last) = match numbers.as_slice() {
+            [number] if protocol_version::matches_name(protocol, number) => (*number, *number),
+            _ => (versions.first()?.version, versions.last()?.version),
+        };
+
+        let oldest = match (has_plugin("viarewind"), has_plugin("viabackwards")) {
+            (true, _) => VIA_REWIND_OLDEST,
+            (false, true) => VIA_BACKWARDS_OLDEST,
+            _ => first,
+        };
+        let newest = match has_plugin("viaversion") {
+            true => protocol_version::latest_release().map_or(last, |v| v.version),
+            false => last,
+        };
+
+        match oldest == newest {
+            true => Some(oldest.to_string()),
+            false => Some(format!("{}–{}", oldest, newest)),
+        }
+    }
+}
+
+impl fmt::Display for ServerFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.software {
+            Some(software) => write!(f, "{}", software)?,
+            None => write!(f, "unknown")?,
+        }
+        if self.proxy {
+            write!(f, " (proxy)")?;
+        }
+        if let Some(ref versions) = self.versions {
+            write!(f, "\n{}", versions)?;
+        }
+        if self.geyser {
+            write!(f, "\nBedrock via Geyser")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minecraft::query::QueryPlugins;
+
+    fn status(json: &str) -> SlpResponse {
+        SlpResponse::from_json(json, &Default::default()).unwrap()
+    }
+
+    fn named(name: &str) -> SlpResponse {
+        status(&format!(
+            r#"{{"version": {{"name": "{}", "protocol": 765}},
+                "players": {{"max": 1, "online": 0}}, "description": ""}}"#,
+            name
+        ))
+    }
+
+    fn query(version: &str, software: Option<&str>, plugins: &[&str]) -> QueryFullStat {
+        QueryFullStat {
+            motd: String::new(),
+            game_type: "SMP".to_string(),
+            game_id: "MINECRAFT".to_string(),
+            version: version.to_string(),
+            plugins: QueryPlugins {
+                server_software: software.map(str::to_string),
+                plugins: plugins.iter().map(|p| p.to_string()).collect(),
+            },
+            map: "world".to_string(),
+            online: 0,
+            max: 1,
+            host_port: 25565,
+            host_ip: "127.0.0.1".to_string(),
+            players: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn detects_proxies() {
+        let fingerprint = ServerFingerprint::analyze(
+            &status(
+                r#"{"version": {"name": "BungeeCord 1.8.x-1.20.x", "protocol": 765},
+                    "players": {"max": 1, "online": 0}, "description": ""}"#,
+            ),
+            Duration::ZERO,
+            None,
+            None,
+        );
+
+        assert_eq!(fingerprint.software, Some(Software::BungeeCord));
+        assert!(fingerprint.proxy);
+        assert_eq!(fingerprint.versions.as_deref(), Some("1.8–1.20"));
+    }
+
+    #[test]
+    fn detects_forks_and_vanilla() {
+        let paper = status(
+            r#"{"version": {"name": "Paper 1.20.4", "protocol": 765},
+                "players": {"max": 1, "online": 0}, "description": ""}"#,
+        );
+        let fingerprint = ServerFingerprint::analyze(&paper, Duration::ZERO, None, None);
+        assert_eq!(fingerprint.software, Some(Software::Paper));
+        assert!(!fingerprint.proxy);
+        assert_eq!(fingerprint.versions.as_deref(), Some("1.20.4"));
+
+        let vanilla = status(
+            r#"{"version": {"name": "1.20.4", "protocol": 765},
+                "players": {"max": 1, "online": 0}, "description": ""}"#,
+        );
+        let fingerprint = ServerFingerprint::analyze(&vanilla, Duration::ZERO, None, None);
+        assert_eq!(fingerprint.software, Some(Software::Vanilla));
+        assert_eq!(fingerprint.versions.as_deref(), Some("1.20.4"));
+    }
+
+    #[test]
+    fn detects_neoforge() {
+        let fingerprint = ServerFingerprint::analyze(
+            &status(
+                r#"{"version": {"name": "1.20.1", "protocol": 763},
+                    "players": {"max": 1, "online": 0}, "description": "",
+                    "forgeData": {"fmlNetworkVersion": 3,
+                        "mods": [{"modId": "neoforge", "modmarker": "47.1.0"}]}}"#,
+            ),
+            Duration::ZERO,
+            None,
+            None,
+        );
+
+        assert_eq!(fingerprint.software, Some(Software::NeoForge));
+    }
+
+    #[test]
+    fn uses_query_plugins() {
+        let query = query(
+            "1.20.4",
+            Some("Paper on Bukkit 1.20.4-R0.1-SNAPSHOT"),
+            &["WorldEdit 7.2.15", "Geyser-Spigot 2.2.0"],
+        );
+        let fingerprint =
+            ServerFingerprint::analyze(&named("1.20.4"), Duration::ZERO, Some(&query), None);
+
+        assert_eq!(fingerprint.software, Some(Software::Paper));
+        assert!(fingerprint
+            .hints
+            .contains(&"Paper in the query".to_string()));
+        assert!(fingerprint.geyser);
+        assert_eq!(fingerprint.versions.as_deref(), Some("1.20.4"));
+    }
+
+    #[test]
+    fn detects_geyser_in_the_version_name() {
+        let fingerprint =
+            ServerFingerprint::analyze(&named("Geyser 1.20.40"), Duration::ZERO, None, None);
+
+        assert!(fingerprint.geyser);
+        assert!(fingerprint.to_string().contains("Bedrock via Geyser"));
+    }
+
+    #[test]
+    fn widens_versions_with_via_plugins() {
+        let latest = protocol_version::latest_release().unwrap().version;
+        let versions = |plugins: &[&str]| {
+            let query = query("1.20.4", Some("Paper on Bukkit 1.20.4"), plugins);
+            ServerFingerprint::analyze(&named("Paper 1.20.4"), Duration::ZERO, Some(&query), None)
+                .versions
+        };
+
+        assert_eq!(versions(&[]).as_deref(), Some("1.20.4"));
+        assert_eq!(
+            versions(&["ViaVersion 4.9.2"]),
+            Some(format!("1.20.4–{}", latest))
+        );
+        assert_eq!(
+            versions(&["ViaVersion 4.9.2", "ViaBackwards 4.9.1"]),
+            Some(format!("1.10–{}", latest))
+        );
+        assert_eq!(
+            versions(&["ViaVersion 4.9.2", "ViaBackwards 4.9.1", "ViaRewind 3.0.5"]),
+            Some(format!("1.7–{}", latest))
+        );
+        // the via plugins for older clients work without viaversion too
+        assert_eq!(
+            versions(&["ViaBackwards 4.9.1"]).as_deref(),
+            Some("1.10–1.20.4")
+        );
+    }
+
+    #[test]
+    fn detects_servers_older_than_1_4() {
+        let legacy = LegacyPingResponse {
+            protocol: None,
+            version: None,
+            motd: "A Minecraft Server".to_string(),
+            online: 0,
+            max: 20,
+        };
+        let status = SlpResponse::from(legacy);
+        let fingerprint =
+            ServerFingerprint::analyze(&status, Duration::ZERO, None, status.legacy.as_ref());
+
+        assert_eq!(fingerprint.software, None);
+        assert_eq!(fingerprint.versions.as_deref(), Some("Beta 1.8–1.3"));
+    }
+
+    #[test]
+    fn prefers_proxy_names_over_backend_names() {
+        // the proxy forwards the status of its backend but answers the query itself
+        let query = query("Velocity 3.3.0", None, &[]);
+        let fingerprint =
+            ServerFingerprint::analyze(&named("Paper 1.20.4"), Duration::ZERO, Some(&query), None);
+
+        assert_eq!(fingerprint.software, Some(Software::Velocity));
+        assert!(fingerprint.proxy);
+        assert!(fingerprint
+            .hints
+            .contains(&"Velocity in the query".to_string()));
+    }
+
+    #[test]
+    fn suspects_relays_from_the_latency() {
+        let relayed = |connect_time: Option<u64>, latency: u64| {
+            let mut status = named("1.20.4");
+            status.connect_time = connect_time.map(Duration::from_millis);
+            ServerFingerprint::analyze(&status, Duration::from_millis(latency), None, None)
+                .hints
+                .iter()
+                .any(|hint| hint.contains("relayed"))
+        };
+
+        assert!(relayed(Some(10), 80));
+        // far away servers are slow to connect to as well
+        assert!(!relayed(Some(150), 160));
+        // jitter on a fast connection
+        assert!(!relayed(Some(1), 15));
+        // behind a socks proxy the handshake isn't measured
+        assert!(!relayed(None, 80));
+    }
+}
@@ -0,0 +1,362 @@
+use std::str::Chars;
+
+use super::text_component::{Style, TextComponent, TextContent};
+
+pub const SECTION_SIGN: char = '§';
+
+pub const COLORS: [(char, &str); 16] = [
+    ('0', "black"),
+    ('1', "dark_blue"),
+    ('2', "dark_green"),
+    ('3', "dark_aqua"),
+    ('4', "dark_red"),
+    ('5', "dark_purple"),
+    ('6', "gold"),
+    ('7', "gray"),
+    ('8', "dark_gray"),
+    ('9', "blue"),
+    ('a', "green"),
+    ('b', "aqua"),
+    ('c', "red"),
+    ('d', "light_purple"),
+    ('e', "yellow"),
+    ('f', "white"),
+];
+
+const OBFUSCATED: char = 'k';
+const BOLD: char = 'l';
+const STRIKETHROUGH: char = 'm';
+const UNDERLINED: char = 'n';
+const ITALIC: char = 'o';
+const RESET: char = 'r';
+// BungeeCord hex colors, §x followed by six §<hex digit>
+const HEX: char = 'x';
+
+const FORMATTING: [char; 5] = [OBFUSCATED, BOLD, STRIKETHROUGH, UNDERLINED, ITALIC];
+
+fn flag_mut(style: &mut Style, code: char) -> Option<&mut Option<bool>> {
+    match code {
+        OBFUSCATED => Some(&mut style.obfuscated),
+        BOLD => Some(&mut style.bold),
+        STRIKETHROUGH => Some(&mut style.strikethrough),
+        UNDERLINED => Some(&mut style.underlined),
+        ITALIC => Some(&mut style.italic),
+        _ => None,
+    }
+}
+
+fn is_enabled(style: &Style, code: char) -> bool {
+    let flag = match code {
+        OBFUSCATED => style.obfuscated,
+        BOLD => style.bold,
+        STRIKETHROUGH => style.strikethrough,
+        UNDERLINED => style.underlined,
+        ITALIC => style.italic,
+        _ => None,
+    };
+    flag.unwrap_or_default()
+}
+
+fn color_code(color: &str) -> Option<String> {
+    if let Some((code, _)) = COLORS.iter().find(|(_, name)| *name == color) {
+        return Some(format!("{SECTION_SIGN}{code}"));
+    }
+
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let mut code = format!("{SECTION_SIGN}{HEX}");
+    for digit in hex.chars() {
+        code.push(SECTION_SIGN);
+        code.push(digit.to_ascii_lowercase());
+    }
+    Some(code)
+}
+
+fn read_hex(chars: &mut Chars) -> Option<String> {
+    let mut lookahead = chars.clone();
+    let mut hex = String::from("#");
+
+    for _ in 0..6 {
+        if lookahead.next()? != SECTION_SIGN {
+            return None;
+        }
+        hex.push(lookahead.next().filter(char::is_ascii_hexdigit)?);
+    }
+
+    *chars = lookahead;
+    Some(hex.to_ascii_lowercase())
+}
+
+/*
+Color codes reset the formatting like on the client.
+Formatting inherited from the base style has to be disabled explicitly then,
+a reset goes back to the base style.
+*/
+fn parse_segments(text: &str, base: &Style) -> Vec<TextComponent> {
+    let mut segments = Vec::new();
+    let mut style = Style::default();
+    let mut current = String::new();
+
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != SECTION_SIGN {
+            current.push(c);
+            continue;
+        }
+
+        let Some(code) = chars.next().map(|c| c.to_ascii_lowercase()) else {
+            break;
+        };
+
+        let color = match code {
+            HEX => read_hex(&mut chars),
+            _ => COLORS
+                .iter()
+                .find(|(c, _)| *c == code)
+                .map(|(_, name)| name.to_string()),
+        };
+
+        let mut next = style.clone();
+        if let Some(color) = color {
+            next = Style {
+                color: Some(color),
+                ..Default::default()
+            };
+
+            for code in FORMATTING
+                .into_iter()
+                .filter(|code| is_enabled(base, *code))
+            {
+                if let Some(flag) = flag_mut(&mut next, code) {
+                    *flag = Some(false);
+                }
+            }
+        } else if code == RESET {
+            next = Style::default();
+        } else if let Some(flag) = flag_mut(&mut next, code) {
+            *flag = Some(true);
+        } else {
+            // unknown codes are dropped by the client
+            continue;
+        }
+
+        if !current.is_empty() {
+            segments.push(TextComponent {
+                content: TextContent::Text(std::mem::take(&mut current)),
+                style: style.clone(),
+                extra: Vec::new(),
+            });
+        }
+        style = next;
+    }
+
+    if !current.is_empty() {
+        segments.push(TextComponent {
+            content: TextContent::Text(current),
+            style,
+            extra: Vec::new(),
+        });
+    }
+
+    segments
+}
+
+/// Parses a string with legacy formatting codes into a component tree.
+pub fn parse(text: &str) -> TextComponent {
+    if !text.contains(SECTION_SIGN) {
+        return TextComponent::text(text);
+    }
+
+    TextComponent {
+        extra: parse_segments(text, &Style::default()),
+        ..Default::default()
+    }
+}
+
+/// Parses legacy formatting codes within the text of a component tree,
+/// which the client renders as well.
+pub fn expand(component: &TextComponent) -> TextComponent {
+    expand_with(component, &Style::default())
+}
+
+fn expand_with(component: &TextComponent, parent: &Style) -> TextComponent {
+    let style = component.style.inherit(parent);
+
+    let mut content = component.content.clone();
+    let mut extra = Vec::new();
+
+    match content {
+        TextContent::Text(ref text) if text.contains(SECTION_SIGN) => {
+            extra = parse_segments(text, &style);
+            content = TextContent::default();
+        }
+        TextContent::Translatable { ref mut with, .. } => {
+            for argument in with.iter_mut() {
+                *argument = expand_with(argument, &style);
+            }
+        }
+        _ => {}
+    }
+
+    extra.extend(
+        component
+            .extra
+            .iter()
+            .map(|child| expand_with(child, &style)),
+    );
+
+    TextComponent {
+        content,
+        style: component.style.clone(),
+        extra,
+    }
+}
+
+/// Only the parts of a style that legacy codes can express, with disabled formatting unset.
+fn formatting(style: &Style) -> Style {
+    let enabled = |flag: Option<bool>| flag.filter(|f| *f);
+
+    Style {
+        color: style.color.clone().filter(|c| color_code(c).is_some()),
+        bold: enabled(style.bold),
+        italic: enabled(style.italic),
+        underlined: enabled(style.underlined),
+        strikethrough: enabled(style.strikethrough),
+        obfuscated: enabled(style.obfuscated),
+        ..Default::default()
+    }
+}
+
+fn codes(formatting: &Style, current: &Style) -> String {
+    let mut codes = String::new();
+    let mut previous = current;
+
+    // formatting can only be added without a reset
+    let only_added = formatting.color == current.color
+        && FORMATTING
+            .into_iter()
+            .all(|code| !is_enabled(current, code) || is_enabled(formatting, code));
+
+    let reset = Style::default();
+    if !only_added {
+        match formatting.color.as_deref().and_then(color_code) {
+            Some(code) => codes.push_str(&code),
+            None => codes.extend([SECTION_SIGN, RESET]),
+        }
+        previous = &reset;
+    }
+
+    for code in FORMATTING {
+        if is_enabled(formatting, code) && !is_enabled(previous, code) {
+            codes.extend([SECTION_SIGN, code]);
+        }
+    }
+
+    codes
+}
+
+/// Serializes a component tree with legacy formatting codes.
+/// Translations are not resolved, see [`TextComponent::translated`].
+pub fn serialize(component: &TextComponent) -> String {
+    let mut serialized = String::new();
+    serialize_into(
+        component,
+        &Style::default(),
+        &mut Style::default(),
+        &mut serialized,
+    );
+    serialized
+}
+
+fn serialize_into(
+    component: &TextComponent,
+    parent: &Style,
+    current: &mut Style,
+    serialized: &mut String,
+) {
+    let style = component.style.inherit(parent);
+
+    let text = component.content_text();
+    if !text.is_empty() {
+        let formatting = formatting(&style);
+        if formatting != *current {
+            serialized.push_str(&codes(&formatting, current));
+            *current = formatting;
+        }
+        serialized.push_str(&text);
+    }
+
+    for child in &component.extra {
+        serialize_into(child, &style, current, serialized);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_codes() {
+        let parsed = parse("§a§lWelcome§r to §x§F§F§0§0§0§0red§ohex §zok");
+        let segments: Vec<(String, Style)> = parsed
+            .extra
+            .iter()
+            .map(|s| (s.content_text(), s.style.clone()))
+            .collect();
+
+        assert_eq!(
+            segments,
+            vec![
+                (
+                    "Welcome".to_string(),
+                    Style {
+                        color: Some("green".into()),
+                        bold: Some(true),
+                        ..Default::default()
+                    }
+                ),
+                (" to ".to_string(), Style::default()),
+                (
+                    "red".to_string(),
+                    Style {
+                        color: Some("#ff0000".into()),
+                        ..Default::default()
+                    }
+                ),
+                (
+                    "hex ok".to_string(),
+                    Style {
+                        color: Some("#ff0000".into()),
+                        italic: Some(true),
+                        ..Default::default()
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn color_disables_inherited_formatting() {
+        let component: TextComponent =
+            serde_json::from_str(r#"{"text": "bold §cred", "bold": true}"#).unwrap();
+        let expanded = expand(&component);
+
+        assert_eq!(expanded.extra[0].style, Style::default());
+        assert_eq!(expanded.extra[1].style.bold, Some(false));
+        assert_eq!(serialize(&expanded), "§lbold §cred");
+    }
+
+    #[test]
+    fn round_trip() {
+        for legacy in [
+            "plain",
+            "§a§lWelcome§r to the §x§1§2§3§4§5§6server",
+            "§6gold §lbold§r §kmagic",
+        ] {
+            assert_eq!(serialize(&parse(legacy)), legacy);
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use std::{error::Error, fmt, io, time::Duration};
+
+/// Caps protecting against servers sending oversized or deeply nested data.
+#[derive(Debug, Clone)]
+pub struct Limits {
+    /// Maximum length of a single frame.
+    pub max_frame_length: usize,
+    /// Maximum nesting of arrays and objects in json responses.
+    pub max_json_depth: usize,
+    /// Maximum length of the base64 encoded favicon.
+    pub max_favicon_size: usize,
+    /// Maximum amount of entries in the mod list.
+    pub max_mod_list_length: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_frame_length: 1024 * 1024,
+            max_json_depth: 64,
+            max_favicon_size: 128 * 1024,
+            max_mod_list_length: 1024,
+        }
+    }
+}
+
+impl Limits {
+    pub fn check_json_depth(&self, json: &str) -> Result<(), LimitExceeded> {
+        let depth = json_depth(json);
+
+        match depth > self.max_json_depth {
+            true => Err(LimitExceeded::JsonDepth {
+                depth,
+                max: self.max_json_depth,
+            }),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Returns the deepest nesting of arrays and objects, without parsing the json.
+pub fn json_depth(json: &str) -> usize {
+    let mut depth: usize = 0;
+    let mut max_depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for byte in json.bytes() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    max_depth
+}
+
+/// Returned inside an [`io::Error`] when a server exceeds one of the [`Limits`] or the read timeout,
+/// use `io::Error::get_ref` and `downcast_ref` to get it back.
+#[derive(Debug)]
+pub enum LimitExceeded {
+    FrameLength { length: usize, max: usize },
+    ReadTimeout(Duration),
+    JsonDepth { depth: usize, max: usize },
+    FaviconSize { size: usize, max: usize },
+    ModListLength { length: usize, max: usize },
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FrameLength { length, max } => {
+                write!(f, "Frame length {} exceeds the limit of {}", length, max)
+            }
+            Self::ReadTimeout(timeout) => {
+                write!(f, "Reading the response took longer than {:?}", timeout)
+            }
+            Self::JsonDepth { depth, max } => {
+                write!(
+                    f,
+                    "Json nesting depth {} exceeds the limit of {}",
+                    depth, max
+                )
+            }
+            Self::FaviconSize { size, max } => {
+                write!(f, "Favicon size {} exceeds the limit of {}", size, max)
+            }
+            Self::ModListLength { length, max } => {
+                write!(f, "Mod list length {} exceeds the limit of {}", length, max)
+            }
+        }
+    }
+}
+
+impl Error for LimitExceeded {}
+
+impl From<LimitExceeded> for io::Error {
+    fn from(value: LimitExceeded) -> Self {
+        let kind = match value {
+            LimitExceeded::ReadTimeout(_) => io::ErrorKind::TimedOut,
+            _ => io::ErrorKind::InvalidData,
+        };
+
+        io::Error::new(kind, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{SinkExt, StreamExt};
+    use tokio::net::TcpListener;
+    use tokio_util::codec::Framed;
+
+    use super::*;
+    use crate::minecraft::{
+        client::{Client, StatusMode},
+        packet::{codec::PacketCodec, slp::SlpResponse},
+    };
+
+    fn limit_of(err: &io::Error) -> Option<&LimitExceeded> {
+        err.get_ref()
+            .and_then(|e| e.downcast_ref::<LimitExceeded>())
+    }
+
+    fn status(extra: &str) -> String {
+        format!(
+            r#"{{"version": {{"name": "1.20.4", "protocol": 765}},
+                "players": {{"max": 20, "online": 0}}, "description": ""{}}}"#,
+            extra
+        )
+    }
+
+    #[test]
+    fn measures_json_depth() {
+        assert_eq!(json_depth(r#""plain""#), 0);
+        assert_eq!(json_depth(r#"{"a": [1, {"b": []}], "c": {}}"#), 4);
+        // brackets inside strings, also after escaped quotes, don't count
+        assert_eq!(json_depth(r#"{"text": "[[[{{{", "b": "\"[[[\\"}"#), 1);
+        assert_eq!(json_depth(r#"["\\", ["x"]]"#), 2);
+    }
+
+    #[test]
+    fn rejects_deep_json() {
+        let limits = Limits {
+            max_json_depth: 4,
+            ..Default::default()
+        };
+
+        let deep = status(r#", "extra": [[[["x"]]]]"#);
+        let err = SlpResponse::from_json(&deep, &limits).unwrap_err();
+        assert!(matches!(
+            limit_of(&err),
+            Some(LimitExceeded::JsonDepth { depth: 5, max: 4 })
+        ));
+
+        assert!(SlpResponse::from_json(&status(r#", "extra": [["x"]]"#), &limits).is_ok());
+    }
+
+    #[test]
+    fn rejects_large_favicons_and_mod_lists() {
+        let limits = Limits {
+            max_favicon_size: 16,
+            max_mod_list_length: 2,
+            ..Default::default()
+        };
+
+        let favicon = status(&format!(r#", "favicon": "{}""#, "A".repeat(17)));
+        let err = SlpResponse::from_json(&favicon, &limits).unwrap_err();
+        assert!(matches!(
+            limit_of(&err),
+            Some(LimitExceeded::FaviconSize { size: 17, max: 16 })
+        ));
+
+        let mod_entry = r#"{"modid": "a", "version": "1"}"#;
+        let mods = |count: usize| {
+            status(&format!(
+                r#", "modinfo": {{"type": "FML", "modList": [{}]}}"#,
+                vec![mod_entry; count].join(",")
+            ))
+        };
+        let err = SlpResponse::from_json(&mods(3), &limits).unwrap_err();
+        assert!(matches!(
+            limit_of(&err),
+            Some(LimitExceeded::ModListLength { length: 3, max: 2 })
+        ));
+        assert!(SlpResponse::from_json(&mods(2), &limits).is_ok());
+    }
+
+    #[tokio::test]
+    async fn passes_the_frame_limit_through_the_client() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // a status response of 113 bytes, well below the default limit
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut server = Framed::new(stream, PacketCodec::default());
+            let _handshake = server.next().await.unwrap().unwrap();
+            let _request = server.next().await.unwrap().unwrap();
+
+            let json = r#"{"version":{"name":"1.20.4","protocol":765},"players":{"max":20,"online":0},"description":"A Minecraft Server"}"#;
+            server
+                .send([&[0x00, json.len() as u8], json.as_bytes()].concat())
+                .await
+                .unwrap();
+        });
+
+        let client = Client::builder("127.0.0.1")
+            .port(port)
+            .mode(StatusMode::Modern)
+            .limits(Limits {
+                max_frame_length: 100,
+                ..Default::default()
+            })
+            .build()
+            .await
+            .unwrap();
+
+        let err = client.status().await.unwrap_err();
+        assert!(matches!(
+            limit_of(&err),
+            Some(LimitExceeded::FrameLength {
+                length: 113,
+                max: 100
+            })
+        ));
+    }
+}
@@ -1,6 +1,19 @@
 pub mod activity;
+pub mod bedrock;
 pub mod client;
+pub mod connection;
+pub mod encryption;
+pub mod fingerprint;
+pub mod legacy_format;
+pub mod limits;
 pub mod packet;
+pub mod protocol_version;
+pub mod query;
+pub mod rcon;
+pub mod render;
+pub mod session;
+pub mod srv;
 pub mod text_component;
+pub mod translation;
 pub mod varint;
-pub mod varstring;
+pub mod varlong;
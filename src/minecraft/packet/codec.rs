@@ -0,0 +1,429 @@
+use std::io::{self, Cursor, Read, Write};
+
+use bytes::{Buf, BufMut, BytesMut};
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::minecraft::{
+    encryption::{PacketCipher, SharedSecret},
+    limits::LimitExceeded,
+    varint::VarInt,
+};
+
+use super::PacketDecode;
+
+/// A single frame with the length prefix removed.
+#[derive(Debug)]
+pub struct RawPacket {
+    pub id: VarInt,
+    pub data: Vec<u8>,
+}
+
+impl RawPacket {
+    pub fn decode<P: PacketDecode>(&self) -> io::Result<P> {
+        if self.id != P::PACKET_ID {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Unexpected packet id {}, expected {}",
+                    self.id.0,
+                    P::PACKET_ID.0
+                ),
+            ));
+        }
+
+        P::decode_packet(&self.data)
+    }
+}
+
+/*
+Length prefixed frames, the packet id is the first field of every frame.
+
+Field Name 	Field Type 	Notes
+Length 	VarInt 	Length of Packet ID + Data
+Packet ID 	VarInt
+Data 	Byte Array
+
+After Set Compression every frame carries the uncompressed length,
+packets below the threshold are sent as is with a data length of 0.
+
+Field Name 	Field Type 	Notes
+Packet Length 	VarInt 	Length of Data Length + compressed Packet ID and Data
+Data Length 	VarInt 	Length of the uncompressed Packet ID and Data, or 0
+Packet ID 	VarInt 	zlib compressed
+Data 	Byte Array 	zlib compressed
+
+Once encryption is enabled the whole stream, including the length prefixes, is encrypted.
+*/
+pub struct PacketCodec {
+    max_length: usize,
+    compression_threshold: Option<usize>,
+    cipher: Option<PacketCipher>,
+    // bytes at the start of the read buffer which are already decrypted
+    decrypted: usize,
+}
+
+impl PacketCodec {
+    // the notchian server never accepts frames with a length prefix above 3 bytes
+    pub const DEFAULT_MAX_LENGTH: usize = 2097151;
+
+    pub fn new(max_length: usize) -> Self {
+        Self {
+            max_length,
+            compression_threshold: None,
+            cipher: None,
+            decrypted: 0,
+        }
+    }
+
+    /// Encrypts every following byte in both directions, as done after the Encryption Response.
+    pub fn enable_encryption(&mut self, shared_secret: &SharedSecret) {
+        self.cipher = Some(PacketCipher::new(shared_secret));
+        self.decrypted = 0;
+    }
+
+    /// Switches to the compressed frame format as announced by Set Compression,
+    /// a negative threshold disables compression again.
+    pub fn set_compression_threshold(&mut self, threshold: i32) {
+        self.compression_threshold = usize::try_from(threshold).ok();
+    }
+
+    pub fn compression_threshold(&self) -> Option<usize> {
+        self.compression_threshold
+    }
+
+    fn decompress(&self, frame: &[u8], threshold: usize) -> io::Result<Vec<u8>> {
+        let mut cursor = Cursor::new(frame);
+        let data_length = VarInt::from_bytes(&mut cursor)?.0;
+        let compressed = &frame[cursor.position() as usize..];
+
+        if data_length == 0 {
+            return Ok(compressed.to_vec());
+        }
+
+        if data_length < 0 || (data_length as usize) < threshold {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Invalid data length {} for compression threshold {}",
+                    data_length, threshold
+                ),
+            ));
+        }
+
+        // the data length is sent by the server, don't trust it for allocations
+        if data_length as usize > self.max_length {
+            return Err(LimitExceeded::FrameLength {
+                length: data_length as usize,
+                max: self.max_length,
+            }
+            .into());
+        }
+
+        let mut data = Vec::with_capacity(data_length as usize);
+        ZlibDecoder::new(compressed)
+            .take(data_length as u64 + 1)
+            .read_to_end(&mut data)?;
+
+        if data.len() != data_length as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Decompressed {} bytes, expected {}",
+                    data.len(),
+                    data_length
+                ),
+            ));
+        }
+
+        Ok(data)
+    }
+}
+
+impl Default for PacketCodec {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX_LENGTH)
+    }
+}
+
+impl Decoder for PacketCodec {
+    type Item = RawPacket;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if let Some(ref mut cipher) = self.cipher {
+            cipher.decrypt(&mut src[self.decrypted..]);
+            self.decrypted = src.len();
+        }
+
+        let (length, prefix_size) = match VarInt::from_partial(src)? {
+            Some(prefix) => prefix,
+            None => return Ok(None),
+        };
+
+        if length.0 <= 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid frame length {}", length.0),
+            ));
+        }
+
+        if length.0 as usize > self.max_length {
+            return Err(LimitExceeded::FrameLength {
+                length: length.0 as usize,
+                max: self.max_length,
+            }
+            .into());
+        }
+
+        let frame_size = prefix_size + length.0 as usize;
+        if src.len() < frame_size {
+            src.reserve(frame_size - src.len());
+            return Ok(None);
+        }
+
+        src.advance(prefix_size);
+        let frame = src.split_to(length.0 as usize);
+        self.decrypted = self.decrypted.saturating_sub(frame_size);
+        let frame = match self.compression_threshold {
+            Some(threshold) => self.decompress(&frame, threshold)?,
+            None => frame.to_vec(),
+        };
+
+        let mut cursor = Cursor::new(&frame[..]);
+        let id = VarInt::from_bytes(&mut cursor)?;
+        let data = frame[cursor.position() as usize..].to_vec();
+
+        log::debug!("received packet id: {}, size: {}", id.0, length.0);
+
+        Ok(Some(RawPacket { id, data }))
+    }
+}
+
+impl Encoder<Vec<u8>> for PacketCodec {
+    type Error = io::Error;
+
+    /// Encodes a packet id followed by its data.
+    fn encode(&mut self, item: Vec<u8>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let item = match self.compression_threshold {
+            Some(threshold) if item.len() >= threshold => {
+                let mut encoder =
+                    ZlibEncoder::new(VarInt(item.len() as i32).to_bytes(), Compression::default());
+                encoder.write_all(&item)?;
+                encoder.finish()?
+            }
+            Some(_) => {
+                let mut data = Vec::with_capacity(item.len() + 1);
+                data.push(0); // uncompressed data length
+                data.extend(item);
+                data
+            }
+            None => item,
+        };
+
+        if item.len() > self.max_length {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Packet of {} bytes exceeds the maximum frame length {}",
+                    item.len(),
+                    self.max_length
+                ),
+            ));
+        }
+
+        let length = VarInt(item.len() as i32).to_bytes();
+        let start = dst.len();
+
+        dst.reserve(length.len() + item.len());
+        dst.put_slice(&length);
+        dst.put_slice(&item);
+
+        if let Some(ref mut cipher) = self.cipher {
+            cipher.encrypt(&mut dst[start..]);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // frames in the wire format of a server with a compression threshold of 256,
+    // a keep alive sent as is and a zlib compressed system chat message
+    const KEEP_ALIVE: &str = "0a00240102030405060708";
+    const SYSTEM_CHAT: &str =
+        "29f702789ccbfccc54ad54925a51a264a594919a9393af509e5f9493a230caa61d5ba9960100c2cd8b66";
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn compressed_codec() -> PacketCodec {
+        let mut codec = PacketCodec::default();
+        codec.set_compression_threshold(256);
+        codec
+    }
+
+    fn decode_one(codec: &mut PacketCodec, frame: &[u8]) -> RawPacket {
+        let mut src = BytesMut::from(frame);
+        let packet = codec.decode(&mut src).unwrap().unwrap();
+        assert!(src.is_empty());
+        packet
+    }
+
+    #[test]
+    fn decodes_captured_frames() {
+        let mut codec = compressed_codec();
+
+        let keep_alive = decode_one(&mut codec, &from_hex(KEEP_ALIVE));
+        assert_eq!(keep_alive.id, VarInt(0x24));
+        assert_eq!(keep_alive.data, 0x0102030405060708i64.to_be_bytes());
+
+        let chat = decode_one(&mut codec, &from_hex(SYSTEM_CHAT));
+        assert_eq!(chat.id, VarInt(0x69));
+        assert_eq!(chat.data.len(), 374);
+        assert!(chat.data.ends_with(b"hello world \"}\0"));
+    }
+
+    #[test]
+    fn round_trips_below_threshold() {
+        let mut codec = compressed_codec();
+
+        let mut dst = BytesMut::new();
+        let packet = [vec![0x24], 0x0102030405060708i64.to_be_bytes().to_vec()].concat();
+        codec.encode(packet, &mut dst).unwrap();
+
+        assert_eq!(&dst[..], &from_hex(KEEP_ALIVE)[..]);
+    }
+
+    #[test]
+    fn round_trips_above_threshold() {
+        let mut codec = compressed_codec();
+
+        let captured = decode_one(&mut codec, &from_hex(SYSTEM_CHAT));
+        let packet = [captured.id.to_bytes(), captured.data.clone()].concat();
+
+        let mut dst = BytesMut::new();
+        codec.encode(packet, &mut dst).unwrap();
+        let decoded = decode_one(&mut codec, &dst);
+
+        assert_eq!(decoded.id, captured.id);
+        assert_eq!(decoded.data, captured.data);
+    }
+
+    #[test]
+    fn round_trips_encrypted_frames_in_pieces() {
+        let secret = [7; 16];
+        let mut sender = compressed_codec();
+        let mut receiver = compressed_codec();
+        sender.enable_encryption(&secret);
+        receiver.enable_encryption(&secret);
+
+        let captured = decode_one(&mut compressed_codec(), &from_hex(SYSTEM_CHAT));
+        let chat = [captured.id.to_bytes(), captured.data.clone()].concat();
+        let keep_alive = from_hex(&KEEP_ALIVE[4..]);
+
+        let mut stream = BytesMut::new();
+        sender.encode(chat, &mut stream).unwrap();
+        sender.encode(keep_alive.clone(), &mut stream).unwrap();
+
+        // feed the encrypted stream a few bytes at a time, like reads from a socket
+        let mut src = BytesMut::new();
+        let mut packets = Vec::new();
+        for piece in stream.chunks(5) {
+            src.extend_from_slice(piece);
+            while let Some(packet) = receiver.decode(&mut src).unwrap() {
+                packets.push(packet);
+            }
+        }
+
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0].data, captured.data);
+        assert_eq!(packets[1].id, VarInt(0x24));
+        assert_eq!(packets[1].data, keep_alive[1..]);
+    }
+
+    #[test]
+    fn rejects_data_length_below_threshold() {
+        let mut codec = compressed_codec();
+
+        let mut frame = from_hex(SYSTEM_CHAT);
+        frame[1..3].copy_from_slice(&[0x80, 0x01]); // data length 128
+
+        let err = codec.decode(&mut BytesMut::from(&frame[..])).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_data_length_above_limit() {
+        let mut codec = PacketCodec::new(128);
+        codec.set_compression_threshold(64);
+
+        let err = codec
+            .decode(&mut BytesMut::from(&from_hex(SYSTEM_CHAT)[..]))
+            .unwrap_err();
+        assert!(err.get_ref().is_some_and(|e| e.is::<LimitExceeded>()));
+    }
+
+    #[test]
+    fn decodes_byte_by_byte() {
+        let mut codec = PacketCodec::default();
+
+        // a two byte length prefix, then a packet that is nothing but its id
+        let large = [vec![0x00], vec![0xAB; 199]].concat();
+        let mut stream = BytesMut::new();
+        codec.encode(large.clone(), &mut stream).unwrap();
+        codec.encode(vec![0x01], &mut stream).unwrap();
+        assert_eq!(&stream[..2], [0xC8, 0x01]);
+        assert_eq!(&stream[stream.len() - 2..], [0x01, 0x01]);
+
+        let mut src = BytesMut::new();
+        let mut packets = Vec::new();
+        for (index, byte) in stream.iter().enumerate() {
+            src.extend_from_slice(&[*byte]);
+            if let Some(packet) = codec.decode(&mut src).unwrap() {
+                packets.push((index, packet));
+            }
+        }
+
+        // nothing comes out before the last byte of a frame
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0].0, 201);
+        assert_eq!(packets[0].1.id, VarInt(0));
+        assert_eq!(packets[0].1.data, large[1..]);
+        assert_eq!(packets[1].0, 203);
+        assert_eq!(packets[1].1.id, VarInt(1));
+        assert!(packets[1].1.data.is_empty());
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_frame_lengths() {
+        // only the length prefix is needed to reject an oversized frame
+        let mut codec = PacketCodec::new(16);
+        let err = codec.decode(&mut BytesMut::from(&[0x11][..])).unwrap_err();
+        assert!(matches!(
+            err.get_ref()
+                .and_then(|e| e.downcast_ref::<LimitExceeded>()),
+            Some(LimitExceeded::FrameLength {
+                length: 17,
+                max: 16
+            })
+        ));
+
+        // a frame of exactly the maximum length is fine
+        let mut src = BytesMut::from(&[vec![0x10], vec![0x00; 16]].concat()[..]);
+        assert!(codec.decode(&mut src).unwrap().is_some());
+
+        for prefix in [&[0x00][..], &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F], &[0xFF; 6]] {
+            let err = codec.decode(&mut BytesMut::from(prefix)).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{:?}", prefix);
+        }
+    }
+}
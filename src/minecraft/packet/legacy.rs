@@ -0,0 +1,269 @@
+use std::io;
+
+use super::slp::{SlpResponse, SlpServerDescription, SlpServerPlayers, SlpServerVersion};
+
+/*
+Legacy server list ping, used before the netty rewrite in 1.7.
+Every variant is answered by a kick packet (0xFF) carrying a UTF-16BE string.
+
+Beta 1.8 - 1.3:  0xFE                               -> "motd§online§max"
+1.4 - 1.5:       0xFE 0x01                          -> "§1\0protocol\0version\0motd\0online\0max"
+1.6:             0xFE 0x01 0xFA + MC|PingHost data  -> same as 1.4
+*/
+pub struct LegacyPingRequest {
+    protocol_version: u8,
+    host: String,
+    port: u16,
+}
+
+impl LegacyPingRequest {
+    pub const PACKET_ID: u8 = 0xFE;
+    pub const PAYLOAD: u8 = 0x01;
+    pub const PLUGIN_MESSAGE_ID: u8 = 0xFA;
+    pub const CHANNEL: &'static str = "MC|PingHost";
+
+    // protocol version of 1.6.4, the last release speaking this protocol
+    pub const VERSION_1_6_4: u8 = 78;
+
+    pub fn new(protocol_version: u8, host: String, port: u16) -> Self {
+        Self {
+            protocol_version,
+            host,
+            port,
+        }
+    }
+}
+
+fn utf16_string(value: &str) -> Vec<u8> {
+    let units: Vec<u16> = value.encode_utf16().collect();
+
+    let mut data = Vec::with_capacity(2 + units.len() * 2);
+    data.extend((units.len() as u16).to_be_bytes());
+    for unit in units {
+        data.extend(unit.to_be_bytes());
+    }
+
+    data
+}
+
+impl From<LegacyPingRequest> for Vec<u8> {
+    fn from(value: LegacyPingRequest) -> Self {
+        // servers older than 1.6 stop reading after the bytes they know,
+        // so the 1.6 form is understood by every legacy server
+        let mut data = vec![
+            LegacyPingRequest::PACKET_ID,
+            LegacyPingRequest::PAYLOAD,
+            LegacyPingRequest::PLUGIN_MESSAGE_ID,
+        ];
+
+        // CHANNEL
+        data.extend(utf16_string(LegacyPingRequest::CHANNEL));
+
+        let host = utf16_string(&value.host);
+
+        // DATA_LENGTH
+        data.extend(((1 + host.len() + 4) as u16).to_be_bytes());
+
+        // PROTOCOL_VERSION
+        data.push(value.protocol_version);
+
+        // HOSTNAME
+        data.extend(host);
+
+        // PORT
+        data.extend((value.port as i32).to_be_bytes());
+
+        data
+    }
+}
+
+///////
+
+#[derive(Debug, Clone)]
+pub struct LegacyPingResponse {
+    pub protocol: Option<i32>, // not sent by servers older than 1.4
+    pub version: Option<String>,
+    pub motd: String,
+    pub online: i32,
+    pub max: i32,
+}
+
+impl LegacyPingResponse {
+    pub const PACKET_ID: u8 = 0xFF;
+    const MAGIC: &'static str = "§1\0";
+
+    fn parse_count(value: &str) -> io::Result<i32> {
+        value.trim().parse::<i32>().map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid player count \"{}\": {}", value, e),
+            )
+        })
+    }
+}
+
+impl TryFrom<Vec<u8>> for LegacyPingResponse {
+    type Error = io::Error;
+
+    /// Parses a complete kick packet, including the packet id and string length.
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        if value.first() != Some(&Self::PACKET_ID) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Expected legacy kick packet",
+            ));
+        }
+
+        let length = match value.get(1..3) {
+            Some(bytes) => u16::from_be_bytes([bytes[0], bytes[1]]) as usize,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Legacy kick packet is too short",
+                ))
+            }
+        };
+
+        let units: Vec<u16> = match value.get(3..3 + length * 2) {
+            Some(bytes) => bytes
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect(),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Legacy kick packet is too short",
+                ))
+            }
+        };
+
+        let text = String::from_utf16(&units)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        log::debug!("legacy ping response: {:?}", text);
+
+        if let Some(fields) = text.strip_prefix(Self::MAGIC) {
+            let fields: Vec<&str> = fields.split('\0').collect();
+
+            if fields.len() < 5 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Legacy ping response is missing fields",
+                ));
+            }
+
+            return Ok(Self {
+                protocol: fields[0].parse().ok(),
+                version: Some(fields[1].to_string()),
+                motd: fields[2].to_string(),
+                online: Self::parse_count(fields[3])?,
+                max: Self::parse_count(fields[4])?,
+            });
+        }
+
+        // beta format, the motd itself cannot contain a section sign
+        let mut fields = text.rsplitn(3, '§');
+        let max = fields.next().unwrap_or_default();
+        let online = fields.next();
+        let motd = fields.next();
+
+        match (motd, online) {
+            (Some(motd), Some(online)) => Ok(Self {
+                protocol: None,
+                version: None,
+                motd: motd.to_string(),
+                online: Self::parse_count(online)?,
+                max: Self::parse_count(max)?,
+            }),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Legacy ping response is missing fields",
+            )),
+        }
+    }
+}
+
+impl From<LegacyPingResponse> for SlpResponse {
+    fn from(value: LegacyPingResponse) -> Self {
+        let legacy = value.clone();
+
+        Self {
+            version: SlpServerVersion {
+                name: value.version.unwrap_or_else(|| "Beta 1.8 - 1.3".into()),
+                protocol: value.protocol.unwrap_or(-1),
+            },
+            players: SlpServerPlayers {
+                max: value.max,
+                online: value.online,
+                sample: None,
+            },
+            description: SlpServerDescription::Simple(value.motd),
+            favicon: None,
+            enforces_secure_chat: None,
+            previews_chat: None,
+            prevents_chat_reports: None,
+            modinfo: None,
+            forge_data: None,
+            srv_target: None,
+            connect_time: None,
+            legacy: Some(legacy),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a kick packet with the text as UTF-16BE
+    fn kick(text: &str) -> Vec<u8> {
+        let units: Vec<u16> = text.encode_utf16().collect();
+
+        let mut data = vec![LegacyPingResponse::PACKET_ID];
+        data.extend((units.len() as u16).to_be_bytes());
+        data.extend(units.iter().flat_map(|unit| unit.to_be_bytes()));
+        data
+    }
+
+    #[test]
+    fn parses_1_4_format() {
+        let response = LegacyPingResponse::try_from(kick(
+            "§1\u{0}127\u{0}1.6.4\u{0}A §aGreen§r MOTD\u{0}3\u{0}20",
+        ))
+        .unwrap();
+
+        assert_eq!(response.protocol, Some(127));
+        assert_eq!(response.version.as_deref(), Some("1.6.4"));
+        assert_eq!(response.motd, "A §aGreen§r MOTD");
+        assert_eq!((response.online, response.max), (3, 20));
+    }
+
+    #[test]
+    fn parses_beta_format() {
+        let data = kick("Old but gold§0§12");
+        assert_eq!(&data[..5], [0xFF, 0x00, 0x11, 0x00, b'O']);
+
+        let response = LegacyPingResponse::try_from(data).unwrap();
+
+        assert_eq!(response.protocol, None);
+        assert_eq!(response.version, None);
+        assert_eq!(response.motd, "Old but gold");
+        assert_eq!((response.online, response.max), (0, 12));
+    }
+
+    #[test]
+    fn rejects_malformed_packets() {
+        let is_error = |data: Vec<u8>| LegacyPingResponse::try_from(data).is_err();
+
+        // wrong packet id, missing length, length past the end
+        assert!(is_error(vec![0x00, 0x00, 0x00]));
+        assert!(is_error(vec![0xFF, 0x00]));
+        assert!(is_error(vec![0xFF, 0x00, 0x05, 0x00, b'a']));
+        // unpaired surrogate
+        assert!(is_error(vec![0xFF, 0x00, 0x01, 0xD8, 0x00]));
+        // missing fields and invalid counts
+        assert!(is_error(kick("§1\u{0}127\u{0}1.6.4\u{0}motd")));
+        assert!(is_error(kick("no counts")));
+        assert!(is_error(kick("motd§many§20")));
+    }
+}
@@ -0,0 +1,256 @@
+use std::io::{self, Read};
+
+use uuid::Uuid;
+
+use crate::minecraft::{text_component::TextComponent, varint::VarInt};
+
+use super::{
+    types::{Decode, Encode, RemainingBytes},
+    Packet,
+};
+
+/*
+Field Name 	Field Type 	Notes
+Name 	String (16)
+Has Sig Data 	Boolean 	1.19 only, always false here
+Has Player UUID 	Boolean 	1.19.1 - 1.20.1
+Player UUID 	UUID 	optional before 1.20.2
+*/
+pub struct LoginStart {
+    protocol_version: VarInt,
+    name: String,
+    uuid: Uuid,
+}
+
+impl Packet for LoginStart {
+    const PACKET_ID: VarInt = VarInt(0x00);
+}
+
+impl LoginStart {
+    pub const VERSION_1_19: i32 = 759;
+    pub const VERSION_1_19_1: i32 = 760;
+    pub const VERSION_1_19_3: i32 = 761;
+    pub const VERSION_1_20_2: i32 = 764;
+
+    /// The fields depend on the protocol version the handshake announced.
+    pub fn new(protocol_version: VarInt, name: String, uuid: Uuid) -> Self {
+        Self {
+            protocol_version,
+            name,
+            uuid,
+        }
+    }
+}
+
+impl Encode for LoginStart {
+    fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        let version = self.protocol_version.0;
+
+        self.name.encode(buf)?;
+
+        // HAS_SIG_DATA
+        if (Self::VERSION_1_19..=Self::VERSION_1_19_1).contains(&version) {
+            false.encode(buf)?;
+        }
+
+        // HAS_PLAYER_UUID
+        if (Self::VERSION_1_19_1..Self::VERSION_1_20_2).contains(&version) {
+            true.encode(buf)?;
+        }
+
+        if version >= Self::VERSION_1_19_1 {
+            self.uuid.encode(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
+///////
+
+#[derive(Packet)]
+#[packet(id = 0x00)]
+pub struct LoginDisconnect {
+    pub json: String,
+}
+
+impl LoginDisconnect {
+    /// Parses the reason, falling back to the raw json as text if it's no valid component.
+    pub fn reason(&self) -> TextComponent {
+        serde_json::from_str(&self.json).unwrap_or_else(|_| TextComponent::text(self.json.clone()))
+    }
+}
+
+///////
+
+/*
+Field Name 	Field Type 	Notes
+Server ID 	String (20) 	empty since 1.7
+Public Key 	Prefixed Byte Array 	DER encoded
+Verify Token 	Prefixed Byte Array
+Should Authenticate 	Boolean 	1.20.5+, offline mode servers may still encrypt
+*/
+pub struct EncryptionRequest {
+    pub server_id: String,
+    pub public_key: Vec<u8>,
+    pub verify_token: Vec<u8>,
+    pub should_authenticate: bool,
+}
+
+impl Packet for EncryptionRequest {
+    const PACKET_ID: VarInt = VarInt(0x01);
+}
+
+impl Decode for EncryptionRequest {
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let server_id = String::decode(reader)?;
+        let public_key = Vec::decode(reader)?;
+        let verify_token = Vec::decode(reader)?;
+
+        // older servers always authenticate
+        let should_authenticate = match bool::decode(reader) {
+            Ok(should_authenticate) => should_authenticate,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => true,
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
+            server_id,
+            public_key,
+            verify_token,
+            should_authenticate,
+        })
+    }
+}
+
+///////
+
+/*
+Field Name 	Field Type 	Notes
+Shared Secret 	Prefixed Byte Array 	encrypted with the public key
+Has Verify Token 	Boolean 	1.19 - 1.19.2, always true here
+Verify Token 	Prefixed Byte Array 	encrypted with the public key
+*/
+pub struct EncryptionResponse {
+    protocol_version: VarInt,
+    shared_secret: Vec<u8>,
+    verify_token: Vec<u8>,
+}
+
+impl Packet for EncryptionResponse {
+    const PACKET_ID: VarInt = VarInt(0x01);
+}
+
+impl EncryptionResponse {
+    pub fn new(protocol_version: VarInt, shared_secret: Vec<u8>, verify_token: Vec<u8>) -> Self {
+        Self {
+            protocol_version,
+            shared_secret,
+            verify_token,
+        }
+    }
+}
+
+impl Encode for EncryptionResponse {
+    fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        self.shared_secret.encode(buf)?;
+
+        // HAS_VERIFY_TOKEN
+        if (LoginStart::VERSION_1_19..LoginStart::VERSION_1_19_3).contains(&self.protocol_version.0)
+        {
+            true.encode(buf)?;
+        }
+
+        self.verify_token.encode(buf)
+    }
+}
+
+///////
+
+/// The profile fields are not needed, the login is complete.
+#[derive(Packet)]
+#[packet(id = 0x02)]
+pub struct LoginSuccess;
+
+///////
+
+/// Switches to the configuration state, 1.20.2+
+#[derive(Packet)]
+#[packet(id = 0x03)]
+pub struct LoginAcknowledged;
+
+///////
+
+#[derive(Packet)]
+#[packet(id = 0x03)]
+pub struct SetCompression {
+    pub threshold: VarInt,
+}
+
+///////
+
+/// Sent by proxies and mod loaders, e.g. for velocity forwarding.
+#[derive(Packet)]
+#[packet(id = 0x04)]
+pub struct LoginPluginRequest {
+    pub message_id: VarInt,
+    pub channel: String,
+    pub data: RemainingBytes,
+}
+
+///////
+
+/// Answers a plugin request, without data the request is marked as not understood.
+#[derive(Packet)]
+#[packet(id = 0x02)]
+pub struct LoginPluginResponse {
+    message_id: VarInt,
+    data: Option<RemainingBytes>,
+}
+
+impl LoginPluginResponse {
+    pub fn not_understood(message_id: VarInt) -> Self {
+        Self {
+            message_id,
+            data: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn login_start_layout_per_version() {
+        let uuid = Uuid::from_u128(0x0123456789abcdef0123456789abcdef);
+        let encode = |protocol: i32| {
+            let mut buf = Vec::new();
+            LoginStart::new(VarInt(protocol), "Steve".into(), uuid)
+                .encode(&mut buf)
+                .unwrap();
+            buf
+        };
+        let name = b"\x05Steve".to_vec();
+        let uuid = uuid.as_bytes().to_vec();
+
+        // 1.18.2, only the name
+        assert_eq!(encode(758), name);
+        // 1.19, no signature data
+        assert_eq!(encode(759), [name.clone(), vec![0x00]].concat());
+        // 1.19.1, no signature data and an optional uuid
+        assert_eq!(
+            encode(760),
+            [name.clone(), vec![0x00, 0x01], uuid.clone()].concat()
+        );
+        // 1.19.3, the signature data is gone
+        assert_eq!(
+            encode(761),
+            [name.clone(), vec![0x01], uuid.clone()].concat()
+        );
+        // 1.20.2+, the uuid is mandatory
+        for protocol in [764, 765, 767] {
+            assert_eq!(encode(protocol), [name.clone(), uuid.clone()].concat());
+        }
+    }
+}
@@ -1,18 +1,61 @@
+pub mod codec;
 pub mod handshake;
+pub mod legacy;
+pub mod login;
+pub mod ping;
 pub mod slp;
+pub mod state;
+pub mod types;
+
+use std::io::{self, Cursor};
+
+pub use helferbiene_derive::Packet;
 
 use crate::minecraft::varint::VarInt;
 
+use self::types::{Decode, Encode};
+
 /*
 Field Name 	Field Type 	Notes
-Length 	VarInt 	Length of Packet ID + Data
 Packet ID 	VarInt
 Data 	Byte Array 	Depends on the connection state and packet ID, see the sections below
+
+The length prefix is handled by the codec.
 */
 pub trait Packet {
     const PACKET_ID: VarInt;
 }
 
-pub trait PacketEncode: Packet + Into<Vec<u8>> {}
+/// Encodes into the packet id followed by the packet data.
+pub trait PacketEncode: Packet + Encode {
+    fn encode_packet(&self) -> io::Result<Vec<u8>> {
+        let mut data = Self::PACKET_ID.to_bytes();
+        self.encode(&mut data)?;
+
+        Ok(data)
+    }
+}
+
+impl<T: Packet + Encode> PacketEncode for T {}
+
+/// Decodes from the packet data, the packet id has already been consumed.
+pub trait PacketDecode: Packet + Decode {
+    fn decode_packet(data: &[u8]) -> io::Result<Self> {
+        let mut cursor = Cursor::new(data);
+        let packet = Self::decode(&mut cursor)?;
+
+        // newer servers may append fields, those are ignored
+        let remaining = data.len() - cursor.position() as usize;
+        if remaining > 0 {
+            log::debug!(
+                "{} unread bytes in packet id {}",
+                remaining,
+                Self::PACKET_ID.0
+            );
+        }
+
+        Ok(packet)
+    }
+}
 
-pub trait PacketDecode: Packet + TryFrom<Vec<u8>> {}
+impl<T: Packet + Decode> PacketDecode for T {}
@@ -0,0 +1,41 @@
+use super::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x01)]
+pub struct PingRequest {
+    payload: i64,
+}
+
+impl PingRequest {
+    pub fn new(payload: i64) -> Self {
+        Self { payload }
+    }
+}
+
+///////
+
+#[derive(Packet)]
+#[packet(id = 0x01)]
+pub struct PingResponse {
+    pub payload: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minecraft::packet::{PacketDecode, PacketEncode};
+
+    #[test]
+    fn ping_pong_round_trip() {
+        let data = PingRequest::new(0x0102030405060708)
+            .encode_packet()
+            .unwrap();
+        assert_eq!(data, [0x01, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]);
+
+        let pong = PingResponse::decode_packet(&data[1..]).unwrap();
+        assert_eq!(pong.payload, 0x0102030405060708);
+
+        // the payload is a full long, a shorter echo is invalid
+        assert!(PingResponse::decode_packet(&data[1..5]).is_err());
+    }
+}
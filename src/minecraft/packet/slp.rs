@@ -1,36 +1,25 @@
-use std::io::{self, Cursor};
+use std::{
+    io::{self, Cursor},
+    time::Duration,
+};
 
-use crate::minecraft::{text_component::TextComponent, varint::VarInt, varstring::VarString};
+use crate::minecraft::{
+    legacy_format,
+    limits::{LimitExceeded, Limits},
+    srv::SrvTarget,
+    text_component::TextComponent,
+    varint::VarInt,
+};
 
-use super::{Packet, PacketDecode, PacketEncode};
+use super::{legacy::LegacyPingResponse, types::Decode, Packet};
 
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
+#[derive(Packet)]
+#[packet(id = 0x00)]
 pub struct SlpRequest;
 
-impl Packet for SlpRequest {
-    const PACKET_ID: VarInt = VarInt(0x00);
-}
-
-impl PacketEncode for SlpRequest {}
-
-impl Into<Vec<u8>> for SlpRequest {
-    fn into(self) -> Vec<u8> {
-        let mut data = Vec::new();
-
-        // PACKET_ID
-        data.extend(Self::PACKET_ID.to_bytes());
-
-        // PACKET_SIZE at the begging
-        let size = VarInt(data.len() as i32);
-
-        data.splice(0..0, size.to_bytes().drain(..));
-
-        data
-    }
-}
-
 ///////
 
 #[skip_serializing_none]
@@ -66,6 +55,16 @@ pub enum SlpServerDescription {
     Complex(TextComponent),
 }
 
+impl SlpServerDescription {
+    /// Both forms as one component tree, with the legacy formatting codes parsed.
+    pub fn to_component(&self) -> TextComponent {
+        match self {
+            Self::Simple(text) => legacy_format::parse(text),
+            Self::Complex(component) => legacy_format::expand(component),
+        }
+    }
+}
+
 #[skip_serializing_none]
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -84,6 +83,142 @@ pub struct SlpForgeModInfo {
     pub mod_list: Vec<SlpForgeModEntry>,
 }
 
+// version marker of mods that are only needed on the server, the real one ends with emojis
+const IGNORE_SERVER_ONLY: &str = "OHNOES";
+
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SlpForgeChannel {
+    pub res: String,
+    pub version: String,
+    pub required: bool,
+}
+
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SlpForgeMod {
+    pub mod_id: String,
+    pub modmarker: Option<String>,
+}
+
+impl SlpForgeMod {
+    /// The version of the mod, `None` for server only mods.
+    pub fn version(&self) -> Option<&str> {
+        self.modmarker
+            .as_deref()
+            .filter(|marker| !marker.starts_with(IGNORE_SERVER_ONLY))
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SlpForgeData {
+    // present on Forge and NeoForge servers since 1.13
+    #[serde(default)]
+    pub channels: Vec<SlpForgeChannel>,
+    #[serde(default)]
+    pub mods: Vec<SlpForgeMod>,
+    pub fml_network_version: Option<i32>,
+    #[serde(default)]
+    pub truncated: bool,
+    // mods and channels packed into a string by 1.18.2+ servers
+    pub d: Option<String>,
+}
+
+impl SlpForgeData {
+    /// Moves the mods and channels packed into `d` over to the other fields.
+    pub fn unpack(&mut self) -> io::Result<()> {
+        let Some(ref d) = self.d else {
+            return Ok(());
+        };
+        let mut reader = Cursor::new(decode_packed(d)?);
+
+        let truncated = bool::decode(&mut reader)?;
+        let mut mods = Vec::new();
+        let mut channels = Vec::new();
+
+        let mod_count = u16::decode(&mut reader)?;
+        for _ in 0..mod_count {
+            // the channel count, shifted past the flag for server only mods
+            let flags = VarInt::decode(&mut reader)?.0;
+            let mod_id = String::decode(&mut reader)?;
+            let modmarker = match flags & 0b1 {
+                0 => Some(String::decode(&mut reader)?),
+                _ => None,
+            };
+
+            for _ in 0..flags >> 1 {
+                let path = String::decode(&mut reader)?;
+                channels.push(SlpForgeChannel {
+                    res: format!("{}:{}", mod_id, path),
+                    version: String::decode(&mut reader)?,
+                    required: bool::decode(&mut reader)?,
+                });
+            }
+
+            mods.push(SlpForgeMod { mod_id, modmarker });
+        }
+
+        let channel_count = VarInt::decode(&mut reader)?.0;
+        for _ in 0..channel_count {
+            channels.push(SlpForgeChannel {
+                res: String::decode(&mut reader)?,
+                version: String::decode(&mut reader)?,
+                required: bool::decode(&mut reader)?,
+            });
+        }
+
+        self.truncated = truncated;
+        self.mods.extend(mods);
+        self.channels.extend(channels);
+        self.d = None;
+        Ok(())
+    }
+}
+
+/// Reverses Forge's packing of bytes into 15 bits per UTF-16 code unit,
+/// the first two units hold the amount of bytes.
+fn decode_packed(packed: &str) -> io::Result<Vec<u8>> {
+    let units: Vec<u16> = packed.encode_utf16().collect();
+
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    let [low, high, data @ ..] = units.as_slice() else {
+        return Err(invalid(
+            "Packed forge data is missing its length".to_string(),
+        ));
+    };
+    let length = (*low as usize & 0x7FFF) | ((*high as usize & 0x7FFF) << 15);
+
+    if length > data.len() * 15 / 8 {
+        return Err(invalid(format!(
+            "Packed forge data claims {} bytes but holds at most {}",
+            length,
+            data.len() * 15 / 8
+        )));
+    }
+
+    let mut bytes = Vec::with_capacity(length);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for unit in data {
+        buffer |= (*unit as u32 & 0x7FFF) << bits;
+        bits += 15;
+
+        while bits >= 8 && bytes.len() < length {
+            bytes.push(buffer as u8);
+            buffer >>= 8;
+            bits -= 8;
+        }
+    }
+
+    Ok(bytes)
+}
+
 #[skip_serializing_none]
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -96,44 +231,156 @@ pub struct SlpResponse {
     pub previews_chat: Option<bool>,
     pub prevents_chat_reports: Option<bool>,
     pub modinfo: Option<SlpForgeModInfo>,
-}
+    pub forge_data: Option<SlpForgeData>,
 
-impl Packet for SlpResponse {
-    const PACKET_ID: VarInt = VarInt(0x00);
+    // not part of the packet, set by the client when the host was resolved via SRV
+    #[serde(skip)]
+    pub srv_target: Option<SrvTarget>,
+    // how long the tcp handshake took, unknown behind a socks proxy
+    #[serde(skip)]
+    pub connect_time: Option<Duration>,
+    // the response the status was converted from if the server only answered the legacy ping
+    #[serde(skip)]
+    pub legacy: Option<LegacyPingResponse>,
 }
 
-impl PacketDecode for SlpResponse {}
+impl SlpResponse {
+    /// Parses the json of a status response, rejecting responses exceeding the limits.
+    pub fn from_json(json: &str, limits: &Limits) -> io::Result<Self> {
+        limits.check_json_depth(json)?;
 
-impl TryFrom<Vec<u8>> for SlpResponse {
-    type Error = io::Error;
+        let mut response: Self = serde_json::from_str(json)?;
 
-    fn try_from(mut value: Vec<u8>) -> Result<Self, Self::Error> {
-        {
-            // remove packet size from value
-            let cursor = Cursor::new(&value);
-            let packet_size = VarInt::from_bytes(cursor)?;
+        if let Some(ref favicon) = response.favicon {
+            if favicon.len() > limits.max_favicon_size {
+                return Err(LimitExceeded::FaviconSize {
+                    size: favicon.len(),
+                    max: limits.max_favicon_size,
+                }
+                .into());
+            }
+        }
 
-            log::debug!("packet size: {}", packet_size.0);
+        if let Some(ref modinfo) = response.modinfo {
+            if modinfo.mod_list.len() > limits.max_mod_list_length {
+                return Err(LimitExceeded::ModListLength {
+                    length: modinfo.mod_list.len(),
+                    max: limits.max_mod_list_length,
+                }
+                .into());
+            }
+        }
+
+        if let Some(ref mut forge_data) = response.forge_data {
+            // a broken packed list shouldn't hide the rest of the status
+            if let Err(e) = forge_data.unpack() {
+                log::warn!("Couldn't unpack forge data: {}", e);
+            }
 
-            let packet_size_var_int_size = packet_size.to_bytes().len();
-            value.drain(0..packet_size_var_int_size);
+            if forge_data.mods.len() > limits.max_mod_list_length {
+                return Err(LimitExceeded::ModListLength {
+                    length: forge_data.mods.len(),
+                    max: limits.max_mod_list_length,
+                }
+                .into());
+            }
         }
 
-        {
-            // remove packet id from value
-            let cursor = Cursor::new(&value);
-            let packet_id = VarInt::from_bytes(cursor)?;
+        Ok(response)
+    }
+}
 
-            log::debug!("packet id: {}", packet_id.0);
+#[derive(Packet)]
+#[packet(id = 0x00)]
+pub struct SlpResponsePacket {
+    pub json: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minecraft::packet::types::Encode;
 
-            let packet_id_var_int_size = packet_id.to_bytes().len();
-            value.drain(0..packet_id_var_int_size);
+    // the encoding forge uses for `d`
+    fn pack(bytes: &[u8]) -> String {
+        let mut units = vec![
+            (bytes.len() & 0x7FFF) as u16,
+            ((bytes.len() >> 15) & 0x7FFF) as u16,
+        ];
+        let mut buffer: u32 = 0;
+        let mut bits = 0;
+
+        for byte in bytes {
+            if bits >= 15 {
+                units.push((buffer & 0x7FFF) as u16);
+                buffer >>= 15;
+                bits -= 15;
+            }
+            buffer |= (*byte as u32) << bits;
+            bits += 8;
+        }
+        while bits > 0 {
+            units.push((buffer & 0x7FFF) as u16);
+            buffer >>= 15;
+            bits -= 15.min(bits);
         }
 
-        let json_str = VarString::from_bytes(value)?;
+        String::from_utf16(&units).unwrap()
+    }
+
+    #[test]
+    fn unpacks_forge_data() {
+        let mut payload = Vec::new();
+        true.encode(&mut payload).unwrap();
+        2u16.encode(&mut payload).unwrap();
+        // forge with one channel
+        VarInt(1 << 1).encode(&mut payload).unwrap();
+        "forge".encode(&mut payload).unwrap();
+        "47.2.0".encode(&mut payload).unwrap();
+        "tier_sorting".encode(&mut payload).unwrap();
+        "1.0".encode(&mut payload).unwrap();
+        false.encode(&mut payload).unwrap();
+        // a server only mod without a version
+        VarInt(0b1).encode(&mut payload).unwrap();
+        "spark".encode(&mut payload).unwrap();
+        VarInt(1).encode(&mut payload).unwrap();
+        "minecraft:register".encode(&mut payload).unwrap();
+        "FML3".encode(&mut payload).unwrap();
+        true.encode(&mut payload).unwrap();
 
-        log::debug!("slp response: {}", json_str.0);
+        let json = serde_json::json!({
+            "version": {"name": "1.20.1", "protocol": 763},
+            "players": {"max": 20, "online": 0},
+            "description": "A Forge server",
+            "forgeData": {"channels": [], "mods": [], "fmlNetworkVersion": 3, "truncated": false, "d": pack(&payload)}
+        });
+        let response = SlpResponse::from_json(&json.to_string(), &Limits::default()).unwrap();
+        let forge_data = response.forge_data.unwrap();
+
+        assert!(forge_data.truncated);
+        assert_eq!(forge_data.fml_network_version, Some(3));
+        let mods: Vec<_> = forge_data
+            .mods
+            .iter()
+            .map(|m| (m.mod_id.as_str(), m.version()))
+            .collect();
+        assert_eq!(mods, [("forge", Some("47.2.0")), ("spark", None)]);
+        let channels: Vec<_> = forge_data
+            .channels
+            .iter()
+            .map(|c| (c.res.as_str(), c.version.as_str(), c.required))
+            .collect();
+        assert_eq!(
+            channels,
+            [
+                ("forge:tier_sorting", "1.0", false),
+                ("minecraft:register", "FML3", true)
+            ]
+        );
+    }
 
-        Ok(serde_json::from_str(&json_str.0)?)
+    #[test]
+    fn rejects_oversized_length() {
+        assert!(decode_packed("\u{7FFF}\u{7FFF}ab").is_err());
     }
 }
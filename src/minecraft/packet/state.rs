@@ -0,0 +1,295 @@
+use std::{error::Error, fmt, io};
+
+use crate::minecraft::varint::VarInt;
+
+use super::{
+    codec::RawPacket,
+    handshake::Handshake,
+    login::{
+        EncryptionRequest, EncryptionResponse, LoginAcknowledged, LoginDisconnect,
+        LoginPluginRequest, LoginPluginResponse, LoginStart, LoginSuccess, SetCompression,
+    },
+    ping::{PingRequest, PingResponse},
+    slp::{SlpRequest, SlpResponsePacket},
+    Packet, PacketEncode,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Handshaking,
+    Status,
+    Login,
+    /// 1.20.2+, between login and play
+    Configuration,
+    Play,
+}
+
+impl fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Handshaking => "handshaking",
+            Self::Status => "status",
+            Self::Login => "login",
+            Self::Configuration => "configuration",
+            Self::Play => "play",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Serverbound,
+    Clientbound,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Serverbound => write!(f, "serverbound"),
+            Self::Clientbound => write!(f, "clientbound"),
+        }
+    }
+}
+
+/// Type level connection state, a connection can only send and receive
+/// the packets registered for its current state.
+pub trait State {
+    const STATE: ConnectionState;
+
+    type Serverbound: ServerboundRegistry;
+    type Clientbound: ClientboundRegistry;
+}
+
+/// Every packet the client may send in a state, each packet converts into the registry.
+pub trait ServerboundRegistry {
+    fn encode_packet(&self) -> io::Result<Vec<u8>>;
+}
+
+/// Every packet the server may send in a state.
+pub trait ClientboundRegistry: Sized {
+    /// Decodes a frame by its packet id, ids not registered for the state are an [`UnknownPacket`].
+    fn decode(packet: &RawPacket) -> io::Result<Self>;
+
+    fn id(&self) -> VarInt;
+}
+
+macro_rules! serverbound {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident($packet:ty)),* $(,)? }) => {
+        $(#[$meta])*
+        pub enum $name {
+            $($variant($packet)),*
+        }
+
+        impl ServerboundRegistry for $name {
+            fn encode_packet(&self) -> io::Result<Vec<u8>> {
+                match *self {
+                    $(Self::$variant(ref packet) => packet.encode_packet(),)*
+                }
+            }
+        }
+
+        $(
+            impl From<$packet> for $name {
+                fn from(packet: $packet) -> Self {
+                    Self::$variant(packet)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! clientbound {
+    ($(#[$meta:meta])* $name:ident: $state:ident { $($variant:ident($packet:ty)),* $(,)? }) => {
+        $(#[$meta])*
+        pub enum $name {
+            $($variant($packet)),*
+        }
+
+        impl ClientboundRegistry for $name {
+            fn decode(packet: &RawPacket) -> io::Result<Self> {
+                $(
+                    if packet.id == <$packet>::PACKET_ID {
+                        return Ok(Self::$variant(packet.decode()?));
+                    }
+                )*
+
+                Err(UnknownPacket {
+                    state: ConnectionState::$state,
+                    direction: Direction::Clientbound,
+                    id: packet.id,
+                }
+                .into())
+            }
+
+            fn id(&self) -> VarInt {
+                match *self {
+                    $(Self::$variant(_) => <$packet>::PACKET_ID,)*
+                }
+            }
+        }
+    };
+}
+
+pub struct Handshaking;
+
+serverbound!(HandshakingServerbound {
+    Handshake(Handshake),
+});
+
+clientbound!(
+    /// The server doesn't answer the handshake.
+    HandshakingClientbound: Handshaking {}
+);
+
+impl State for Handshaking {
+    const STATE: ConnectionState = ConnectionState::Handshaking;
+
+    type Serverbound = HandshakingServerbound;
+    type Clientbound = HandshakingClientbound;
+}
+
+pub struct Status;
+
+serverbound!(StatusServerbound {
+    Request(SlpRequest),
+    Ping(PingRequest),
+});
+
+clientbound!(StatusClientbound: Status {
+    Response(SlpResponsePacket),
+    Pong(PingResponse),
+});
+
+impl State for Status {
+    const STATE: ConnectionState = ConnectionState::Status;
+
+    type Serverbound = StatusServerbound;
+    type Clientbound = StatusClientbound;
+}
+
+pub struct Login;
+
+serverbound!(LoginServerbound {
+    Start(LoginStart),
+    EncryptionResponse(EncryptionResponse),
+    PluginResponse(LoginPluginResponse),
+    Acknowledged(LoginAcknowledged),
+});
+
+clientbound!(LoginClientbound: Login {
+    Disconnect(LoginDisconnect),
+    EncryptionRequest(EncryptionRequest),
+    Success(LoginSuccess),
+    SetCompression(SetCompression),
+    PluginRequest(LoginPluginRequest),
+});
+
+impl State for Login {
+    const STATE: ConnectionState = ConnectionState::Login;
+
+    type Serverbound = LoginServerbound;
+    type Clientbound = LoginClientbound;
+}
+
+// the packet ids of the following states change between protocol versions,
+// none are registered yet and frames have to be read with `Connection::recv_raw`
+
+pub struct Configuration;
+
+serverbound!(ConfigurationServerbound {});
+
+clientbound!(ConfigurationClientbound: Configuration {});
+
+impl State for Configuration {
+    const STATE: ConnectionState = ConnectionState::Configuration;
+
+    type Serverbound = ConfigurationServerbound;
+    type Clientbound = ConfigurationClientbound;
+}
+
+pub struct Play;
+
+serverbound!(PlayServerbound {});
+
+clientbound!(PlayClientbound: Play {});
+
+impl State for Play {
+    const STATE: ConnectionState = ConnectionState::Play;
+
+    type Serverbound = PlayServerbound;
+    type Clientbound = PlayClientbound;
+}
+
+/// Returned inside an [`io::Error`] when a packet id isn't registered for the connection state.
+#[derive(Debug)]
+pub struct UnknownPacket {
+    pub state: ConnectionState,
+    pub direction: Direction,
+    pub id: VarInt,
+}
+
+impl fmt::Display for UnknownPacket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unknown {} packet id {:#04x} in the {} state",
+            self.direction, self.id.0, self.state
+        )
+    }
+}
+
+impl Error for UnknownPacket {}
+
+impl From<UnknownPacket> for io::Error {
+    fn from(value: UnknownPacket) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_by_state() {
+        // 0x00 is a status response in the status state and a disconnect in the login state
+        let json = "{}";
+        let packet = RawPacket {
+            id: VarInt(0x00),
+            data: [vec![json.len() as u8], json.as_bytes().to_vec()].concat(),
+        };
+
+        assert!(matches!(
+            StatusClientbound::decode(&packet),
+            Ok(StatusClientbound::Response(_))
+        ));
+        assert!(matches!(
+            LoginClientbound::decode(&packet),
+            Ok(LoginClientbound::Disconnect(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_packet_ids() {
+        let packet = RawPacket {
+            id: VarInt(0x05),
+            data: Vec::new(),
+        };
+
+        let err = StatusClientbound::decode(&packet).err().unwrap();
+        let unknown = err
+            .get_ref()
+            .unwrap()
+            .downcast_ref::<UnknownPacket>()
+            .unwrap();
+
+        assert_eq!(unknown.state, ConnectionState::Status);
+        assert_eq!(unknown.id, VarInt(0x05));
+        assert_eq!(
+            err.to_string(),
+            "Unknown clientbound packet id 0x05 in the status state"
+        );
+    }
+}
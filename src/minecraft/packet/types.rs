@@ -0,0 +1,288 @@
+use std::io::{self, Read};
+
+use uuid::Uuid;
+
+use crate::minecraft::{varint::VarInt, varlong::VarLong};
+
+/// Writes a protocol data type, fields are written back to back without separators.
+pub trait Encode {
+    fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()>;
+}
+
+/// Reads a protocol data type, the reader is positioned right after the previous field.
+pub trait Decode: Sized {
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self>;
+}
+
+impl Encode for VarInt {
+    fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        buf.extend(self.to_bytes());
+        Ok(())
+    }
+}
+
+impl Decode for VarInt {
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        VarInt::from_bytes(reader)
+    }
+}
+
+impl Encode for VarLong {
+    fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        buf.extend(self.to_bytes());
+        Ok(())
+    }
+}
+
+impl Decode for VarLong {
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        VarLong::from_bytes(reader)
+    }
+}
+
+macro_rules! impl_number {
+    ($($type:ty),*) => {
+        $(
+            impl Encode for $type {
+                fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+                    buf.extend(self.to_be_bytes());
+                    Ok(())
+                }
+            }
+
+            impl Decode for $type {
+                fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+                    let mut bytes = [0; std::mem::size_of::<$type>()];
+                    reader.read_exact(&mut bytes)?;
+                    Ok(Self::from_be_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+impl_number!(u8, i8, u16, i16, i32, i64, f32, f64);
+
+impl Encode for bool {
+    fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        buf.push(*self as u8);
+        Ok(())
+    }
+}
+
+impl Decode for bool {
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        match u8::decode(reader)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            byte => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid boolean {:#04x}", byte),
+            )),
+        }
+    }
+}
+
+impl Encode for Uuid {
+    fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        buf.extend(self.as_bytes());
+        Ok(())
+    }
+}
+
+impl Decode for Uuid {
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut bytes = [0; 16];
+        reader.read_exact(&mut bytes)?;
+        Ok(Self::from_bytes(bytes))
+    }
+}
+
+// reads a VarInt length prefix followed by as many bytes,
+// the size is bounded by the frame length limit of the codec
+fn read_prefixed<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let length = VarInt::decode(reader)?.0;
+
+    if length < 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid length prefix {}", length),
+        ));
+    }
+
+    // the length is sent by the server, don't trust it for allocations
+    let mut data = Vec::new();
+    reader.take(length as u64).read_to_end(&mut data)?;
+
+    if data.len() != length as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("Expected {} bytes, got {}", length, data.len()),
+        ));
+    }
+
+    Ok(data)
+}
+
+impl Encode for str {
+    fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        self.as_bytes().encode(buf)
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        self.as_str().encode(buf)
+    }
+}
+
+impl Decode for String {
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let data = read_prefixed(reader)?;
+
+        String::from_utf8(data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Length prefixed byte array.
+impl Encode for [u8] {
+    fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        VarInt(self.len() as i32).encode(buf)?;
+        buf.extend(self);
+        Ok(())
+    }
+}
+
+impl Encode for Vec<u8> {
+    fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        self.as_slice().encode(buf)
+    }
+}
+
+impl Decode for Vec<u8> {
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        read_prefixed(reader)
+    }
+}
+
+/// Byte array without length prefix, taking up the rest of the packet.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RemainingBytes(pub Vec<u8>);
+
+impl Encode for RemainingBytes {
+    fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        buf.extend(&self.0);
+        Ok(())
+    }
+}
+
+impl Decode for RemainingBytes {
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Ok(Self(data))
+    }
+}
+
+/// Boolean prefixed optional value.
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            Some(value) => {
+                true.encode(buf)?;
+                value.encode(buf)
+            }
+            None => false.encode(buf),
+        }
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        match bool::decode(reader)? {
+            true => Ok(Some(T::decode(reader)?)),
+            false => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::minecraft::packet::{Packet, PacketDecode, PacketEncode};
+
+    #[derive(Packet, Debug, PartialEq)]
+    #[packet(id = 0x2a)]
+    struct Everything {
+        varint: VarInt,
+        varlong: VarLong,
+        string: String,
+        port: u16,
+        long: i64,
+        flag: bool,
+        uuid: Uuid,
+        bytes: Vec<u8>,
+        some: Option<String>,
+        none: Option<i64>,
+        rest: RemainingBytes,
+    }
+
+    #[test]
+    fn derived_packet_round_trips() {
+        let packet = Everything {
+            varint: VarInt(-1),
+            varlong: VarLong(i64::MIN),
+            string: "grüße".into(),
+            port: 25565,
+            long: -2,
+            flag: true,
+            uuid: Uuid::from_u128(0x0123456789abcdef0123456789abcdef),
+            bytes: vec![1, 2, 3],
+            some: Some("x".into()),
+            none: None,
+            rest: RemainingBytes(vec![4, 5]),
+        };
+
+        let data = packet.encode_packet().unwrap();
+        assert_eq!(data[0], 0x2a);
+        assert_eq!(Everything::decode_packet(&data[1..]).unwrap(), packet);
+    }
+
+    #[test]
+    fn known_encodings() {
+        let mut buf = Vec::new();
+        VarInt(2097151).encode(&mut buf).unwrap();
+        VarLong(-1).encode(&mut buf).unwrap();
+        "mc".encode(&mut buf).unwrap();
+        25565u16.encode(&mut buf).unwrap();
+
+        assert_eq!(
+            buf,
+            [
+                &[0xff, 0xff, 0x7f][..],
+                &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01],
+                &[0x02, b'm', b'c'],
+                &[0x63, 0xdd],
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_and_invalid_data() {
+        // length prefix claims more bytes than available
+        let mut reader = Cursor::new([0x05, b'a']);
+        assert_eq!(
+            String::decode(&mut reader).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+
+        let mut reader = Cursor::new([0x02]);
+        assert_eq!(
+            bool::decode(&mut reader).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
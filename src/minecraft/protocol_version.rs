@@ -0,0 +1,92 @@
+/// A release or snapshot and the protocol number it speaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolVersion {
+    pub protocol: i32,
+    pub version: &'static str,
+    pub snapshot: bool,
+    /// YYYY-MM-DD
+    pub release_date: &'static str,
+}
+
+// generated by build.rs from data/protocol_versions.csv, sorted by protocol number
+include!(concat!(env!("OUT_DIR"), "/protocol_versions.rs"));
+
+/// Returns every version speaking the protocol, oldest first.
+pub fn lookup(protocol: i32) -> &'static [ProtocolVersion] {
+    let start = PROTOCOL_VERSIONS.partition_point(|v| v.protocol < protocol);
+    let end = PROTOCOL_VERSIONS.partition_point(|v| v.protocol <= protocol);
+
+    &PROTOCOL_VERSIONS[start..end]
+}
+
+/// Formats the versions speaking the protocol, e.g. "1.20.3–1.20.4".
+pub fn version_range(protocol: i32) -> Option<String> {
+    let versions = lookup(protocol);
+
+    match (versions.first(), versions.last()) {
+        (Some(first), Some(last)) if first == last => Some(first.version.to_string()),
+        (Some(first), Some(last)) => Some(format!("{}–{}", first.version, last.version)),
+        _ => None,
+    }
+}
+
+/// The newest release in the table, snapshots are skipped.
+pub fn latest_release() -> Option<&'static ProtocolVersion> {
+    PROTOCOL_VERSIONS.iter().rev().find(|v| !v.snapshot)
+}
+
+/// Extracts version numbers like "1.20.4" or "1.8" from a version name.
+pub fn version_numbers(name: &str) -> Vec<&str> {
+    name.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .map(|part| part.trim_matches('.'))
+        .filter(|part| part.contains('.') && !part.contains(".."))
+        .collect()
+}
+
+/// Checks whether the version name reported by a server fits its protocol number.
+/// Names without a version number, e.g. "Velocity", and unknown protocols are accepted.
+pub fn matches_name(protocol: i32, name: &str) -> bool {
+    let versions = lookup(protocol);
+    let numbers = version_numbers(name);
+
+    if versions.is_empty() || numbers.is_empty() {
+        return true;
+    }
+
+    // "1.20" stands for the whole 1.20.x line, "1.8.x" ends up as "1.8"
+    numbers.iter().any(|number| {
+        versions
+            .iter()
+            .any(|v| v.version == *number || v.version.starts_with(&format!("{}.", number)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_version_ranges() {
+        assert_eq!(version_range(765).as_deref(), Some("1.20.3–1.20.4"));
+        assert_eq!(version_range(764).as_deref(), Some("1.20.2"));
+        assert_eq!(version_range(47).as_deref(), Some("1.8–1.8.9"));
+        assert_eq!(version_range(-1), None);
+    }
+
+    #[test]
+    fn table_is_sorted() {
+        assert!(PROTOCOL_VERSIONS
+            .windows(2)
+            .all(|w| w[0].protocol <= w[1].protocol));
+    }
+
+    #[test]
+    fn matches_reported_names() {
+        assert!(matches_name(765, "Paper 1.20.4"));
+        assert!(matches_name(765, "Requires MC 1.8 / 1.20"));
+        assert!(matches_name(47, "BungeeCord 1.8.x-1.20.x"));
+        assert!(matches_name(765, "Velocity"));
+        assert!(!matches_name(47, "Paper 1.20.4"));
+        assert!(!matches_name(765, "1.2"));
+    }
+}
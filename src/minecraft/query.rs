@@ -0,0 +1,432 @@
+use std::{io, net::SocketAddr, time::Duration};
+
+use tokio::{
+    net::{self, UdpSocket},
+    time,
+};
+
+/*
+GameSpy4 query protocol, enabled with `enable-query=true` in server.properties.
+
+Request:  0xFE 0xFD | type | session id (i32) | payload
+Response: type | session id (i32) | payload
+*/
+const MAGIC: [u8; 2] = [0xFE, 0xFD];
+
+const TYPE_HANDSHAKE: u8 = 0x09;
+const TYPE_STAT: u8 = 0x00;
+
+// only the lower 4 bits of every byte are used by the server
+const SESSION_ID_MASK: i32 = 0x0F0F0F0F;
+
+// constant padding in front of the key/value section and the player section of a full stat
+const FULL_STAT_PADDING: &[u8] = b"splitnum\x00\x80\x00";
+const PLAYER_PADDING: &[u8] = b"\x01player_\x00\x00";
+
+#[derive(Debug)]
+pub struct QueryBasicStat {
+    pub motd: String,
+    pub game_type: String,
+    pub map: String,
+    pub online: i32,
+    pub max: i32,
+    pub host_port: u16,
+    pub host_ip: String,
+}
+
+#[derive(Debug, Default)]
+pub struct QueryPlugins {
+    pub server_software: Option<String>,
+    pub plugins: Vec<String>,
+}
+
+impl QueryPlugins {
+    /// Parses the `plugins` value, e.g. `Paper on Bukkit 1.20.4: WorldEdit 7.2.15; LuckPerms 5.4.102`.
+    /// Vanilla servers send an empty string.
+    fn parse(value: &str) -> Self {
+        if value.is_empty() {
+            return Self::default();
+        }
+
+        let (server_software, plugins) = match value.split_once(": ") {
+            Some((software, plugins)) => (software, plugins),
+            None => (value, ""),
+        };
+
+        Self {
+            server_software: Some(server_software.to_string()),
+            plugins: plugins
+                .split("; ")
+                .filter(|p| !p.is_empty())
+                .map(|p| p.to_string())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct QueryFullStat {
+    pub motd: String,
+    pub game_type: String,
+    pub game_id: String,
+    pub version: String,
+    pub plugins: QueryPlugins,
+    pub map: String,
+    pub online: i32,
+    pub max: i32,
+    pub host_port: u16,
+    pub host_ip: String,
+    pub players: Vec<String>,
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> io::Result<T> {
+    value.parse().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid {} \"{}\" in query response", name, value),
+        )
+    })
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn string(&mut self) -> io::Result<String> {
+        match self.data.iter().position(|b| *b == 0) {
+            Some(end) => {
+                let value = String::from_utf8_lossy(&self.data[..end]).into_owned();
+                self.data = &self.data[end + 1..];
+                Ok(value)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Unterminated string in query response",
+            )),
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&mut self, name: &str) -> io::Result<T> {
+        parse_number(name, &self.string()?)
+    }
+
+    fn skip(&mut self, expected: &[u8]) -> io::Result<()> {
+        match self.data.strip_prefix(expected) {
+            Some(rest) => {
+                self.data = rest;
+                Ok(())
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Unexpected padding in query response",
+            )),
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for QueryBasicStat {
+    type Error = io::Error;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let mut reader = Reader { data: value };
+
+        let motd = reader.string()?;
+        let game_type = reader.string()?;
+        let map = reader.string()?;
+        let online = reader.number("player count")?;
+        let max = reader.number("max player count")?;
+
+        // the port is the only little endian field of the protocol
+        let host_port = match reader.data.get(0..2) {
+            Some(bytes) => u16::from_le_bytes([bytes[0], bytes[1]]),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Basic stat is too short",
+                ))
+            }
+        };
+        reader.data = &reader.data[2..];
+
+        let host_ip = reader.string()?;
+
+        Ok(Self {
+            motd,
+            game_type,
+            map,
+            online,
+            max,
+            host_port,
+            host_ip,
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for QueryFullStat {
+    type Error = io::Error;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let mut reader = Reader { data: value };
+
+        reader.skip(FULL_STAT_PADDING)?;
+
+        let mut values = Vec::new();
+        loop {
+            let key = reader.string()?;
+            if key.is_empty() {
+                break;
+            }
+
+            values.push((key, reader.string()?));
+        }
+
+        let get = |key: &str| {
+            values
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+                .unwrap_or_default()
+        };
+
+        reader.skip(PLAYER_PADDING)?;
+
+        let mut players = Vec::new();
+        loop {
+            let player = reader.string()?;
+            if player.is_empty() {
+                break;
+            }
+
+            players.push(player);
+        }
+
+        Ok(Self {
+            motd: get("hostname"),
+            game_type: get("gametype"),
+            game_id: get("game_id"),
+            version: get("version"),
+            plugins: QueryPlugins::parse(&get("plugins")),
+            map: get("map"),
+            online: parse_number("numplayers", &get("numplayers"))?,
+            max: parse_number("maxplayers", &get("maxplayers"))?,
+            host_port: parse_number("hostport", &get("hostport"))?,
+            host_ip: get("hostip"),
+            players,
+        })
+    }
+}
+
+///////
+
+pub struct QueryClient {
+    host: String,
+    port: u16,
+    timeout: Duration,
+}
+
+impl QueryClient {
+    const ATTEMPTS: u32 = 3;
+    const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+    pub async fn new(host: String, port: u16) -> io::Result<Self> {
+        _ = Self::lookup(&host, port).await?;
+
+        Ok(Self {
+            host,
+            port,
+            timeout: Self::DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Timeout of a single request attempt.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn lookup(host: &str, port: u16) -> io::Result<SocketAddr> {
+        match net::lookup_host(format!("{}:{}", host, port)).await?.next() {
+            Some(addr) => Ok(addr),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "Hostname doesn't resolve to address",
+            )),
+        }
+    }
+
+    pub async fn basic_stat(&self) -> io::Result<QueryBasicStat> {
+        let (socket, session_id, token) = self.handshake().await?;
+
+        let payload = token.to_be_bytes();
+        let response = self
+            .request(&socket, TYPE_STAT, session_id, &payload)
+            .await?;
+
+        QueryBasicStat::try_from(response.as_slice())
+    }
+
+    pub async fn full_stat(&self) -> io::Result<QueryFullStat> {
+        let (socket, session_id, token) = self.handshake().await?;
+
+        // the additional 4 padding bytes request the full stat
+        let mut payload = token.to_be_bytes().to_vec();
+        payload.extend([0; 4]);
+
+        let response = self
+            .request(&socket, TYPE_STAT, session_id, &payload)
+            .await?;
+
+        QueryFullStat::try_from(response.as_slice())
+    }
+
+    async fn handshake(&self) -> io::Result<(UdpSocket, i32, i32)> {
+        let addr = Self::lookup(&self.host, self.port).await?;
+
+        let bind_addr = match addr {
+            SocketAddr::V4(_) => "0.0.0.0:0",
+            SocketAddr::V6(_) => "[::]:0",
+        };
+        let socket = UdpSocket::bind(bind_addr).await?;
+        socket.connect(addr).await?;
+
+        let session_id = rand::random::<i32>() & SESSION_ID_MASK;
+
+        let response = self
+            .request(&socket, TYPE_HANDSHAKE, session_id, &[])
+            .await?;
+
+        // the challenge token is sent as a null terminated decimal string
+        let token = Reader {
+            data: response.as_slice(),
+        }
+        .number::<i32>("challenge token")?;
+
+        log::debug!("query challenge token: {}", token);
+
+        Ok((socket, session_id, token))
+    }
+
+    /// Sends a request and returns the response payload, retrying on lost datagrams.
+    async fn request(
+        &self,
+        socket: &UdpSocket,
+        type_: u8,
+        session_id: i32,
+        payload: &[u8],
+    ) -> io::Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(7 + payload.len());
+        data.extend(MAGIC);
+        data.push(type_);
+        data.extend(session_id.to_be_bytes());
+        data.extend(payload);
+
+        let mut buf = vec![0; 65535];
+
+        for attempt in 1..=Self::ATTEMPTS {
+            socket.send(data.as_slice()).await?;
+
+            let n = match time::timeout(self.timeout, socket.recv(&mut buf)).await {
+                Ok(res) => res?,
+                Err(_) => {
+                    log::debug!("query request attempt {} timed out", attempt);
+                    continue;
+                }
+            };
+
+            let response = &buf[0..n];
+            if response.len() < 5
+                || response[0] != type_
+                || response[1..5] != session_id.to_be_bytes()
+            {
+                log::debug!("ignoring unexpected query response");
+                continue;
+            }
+
+            return Ok(response[5..].to_vec());
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "Server didn't answer the query request",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_basic_stat() {
+        let data = b"A Minecraft Server\0SMP\0world\0\x32\0\x320\0\xdd\x63127.0.0.1\0";
+        let stat = QueryBasicStat::try_from(&data[..]).unwrap();
+
+        assert_eq!(stat.motd, "A Minecraft Server");
+        assert_eq!(
+            (stat.game_type.as_str(), stat.map.as_str()),
+            ("SMP", "world")
+        );
+        assert_eq!((stat.online, stat.max), (2, 20));
+        assert_eq!(stat.host_port, 25565);
+        assert_eq!(stat.host_ip, "127.0.0.1");
+
+        assert!(QueryBasicStat::try_from(&data[..30]).is_err());
+    }
+
+    #[test]
+    fn parses_full_stat() {
+        let mut data = FULL_STAT_PADDING.to_vec();
+        for (key, value) in [
+            ("hostname", "A Minecraft Server"),
+            ("gametype", "SMP"),
+            ("game_id", "MINECRAFT"),
+            ("version", "1.20.4"),
+            (
+                "plugins",
+                "Paper on Bukkit 1.20.4: WorldEdit 7.2.15; LuckPerms 5.4.102",
+            ),
+            ("map", "world"),
+            ("numplayers", "2"),
+            ("maxplayers", "20"),
+            ("hostport", "25565"),
+            ("hostip", "127.0.0.1"),
+        ] {
+            data.extend(key.as_bytes());
+            data.push(0);
+            data.extend(value.as_bytes());
+            data.push(0);
+        }
+        data.push(0);
+        data.extend(PLAYER_PADDING);
+        data.extend(b"Notch\0jeb_\0\0");
+
+        let stat = QueryFullStat::try_from(data.as_slice()).unwrap();
+
+        assert_eq!(stat.motd, "A Minecraft Server");
+        assert_eq!(stat.game_id, "MINECRAFT");
+        assert_eq!(stat.version, "1.20.4");
+        assert_eq!(
+            stat.plugins.server_software.as_deref(),
+            Some("Paper on Bukkit 1.20.4")
+        );
+        assert_eq!(
+            stat.plugins.plugins,
+            ["WorldEdit 7.2.15", "LuckPerms 5.4.102"]
+        );
+        assert_eq!((stat.online, stat.max, stat.host_port), (2, 20, 25565));
+        assert_eq!(stat.players, ["Notch", "jeb_"]);
+
+        // cut off inside the player section, and a broken padding
+        assert!(QueryFullStat::try_from(&data[..data.len() - 3]).is_err());
+        data[0] = b'x';
+        assert!(QueryFullStat::try_from(data.as_slice()).is_err());
+    }
+
+    #[test]
+    fn parses_vanilla_plugins() {
+        let plugins = QueryPlugins::parse("");
+        assert_eq!(plugins.server_software, None);
+        assert!(plugins.plugins.is_empty());
+    }
+}
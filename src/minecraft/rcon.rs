@@ -0,0 +1,336 @@
+use std::{io, time::Duration};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time,
+};
+
+use crate::minecraft::{connection::Transport, limits::LimitExceeded};
+
+pub const DEFAULT_PORT: u16 = 25575;
+
+/*
+Source RCON protocol, all integers are little endian.
+
+Field Name 	Field Type 	Notes
+Length 	i32 	Length of the remainder of the packet
+Request ID 	i32 	Echoed back by the server, -1 on failed authentication
+Type 	i32 	3: login, 2: command, 0: command response
+Payload 	Null-terminated ASCII String
+Padding 	Byte 	0x00
+*/
+#[derive(Debug)]
+pub struct RconPacket {
+    pub request_id: i32,
+    pub type_: i32,
+    pub payload: String,
+}
+
+impl RconPacket {
+    pub const TYPE_LOGIN: i32 = 3;
+    pub const TYPE_COMMAND: i32 = 2;
+    pub const TYPE_RESPONSE: i32 = 0;
+
+    // the notchian server rejects larger client packets
+    pub const MAX_PAYLOAD_SIZE: usize = 1446;
+    // responses are split into fragments of 4096 chars, which are up to 4 bytes each in utf8
+    pub const MAX_RESPONSE_PAYLOAD_SIZE: usize = 4096 * 4;
+
+    const HEADER_SIZE: usize = 4 + 4 + 2; // request id, type and the two null bytes
+
+    pub fn new(request_id: i32, type_: i32, payload: String) -> Self {
+        Self {
+            request_id,
+            type_,
+            payload,
+        }
+    }
+}
+
+impl From<RconPacket> for Vec<u8> {
+    fn from(value: RconPacket) -> Self {
+        let mut data = Vec::with_capacity(4 + RconPacket::HEADER_SIZE + value.payload.len());
+
+        data.extend(((RconPacket::HEADER_SIZE + value.payload.len()) as i32).to_le_bytes());
+        data.extend(value.request_id.to_le_bytes());
+        data.extend(value.type_.to_le_bytes());
+        data.extend(value.payload.as_bytes());
+        data.extend([0, 0]);
+
+        data
+    }
+}
+
+impl TryFrom<Vec<u8>> for RconPacket {
+    type Error = io::Error;
+
+    /// Parses a packet without its length prefix.
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        if value.len() < Self::HEADER_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "RCON packet is too short",
+            ));
+        }
+
+        let request_id = i32::from_le_bytes(value[0..4].try_into().unwrap());
+        let type_ = i32::from_le_bytes(value[4..8].try_into().unwrap());
+
+        let payload = &value[8..value.len() - 2];
+        let payload = match payload.iter().position(|b| *b == 0) {
+            Some(end) => &payload[..end],
+            None => payload,
+        };
+
+        Ok(Self {
+            request_id,
+            type_,
+            payload: String::from_utf8_lossy(payload).into_owned(),
+        })
+    }
+}
+
+///////
+
+pub struct RconClient {
+    stream: Box<dyn Transport>,
+    next_id: i32,
+}
+
+impl RconClient {
+    const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+    // a silent server would otherwise keep the deferred interaction waiting forever
+    const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Connects and authenticates with the given password.
+    pub async fn connect(host: &str, port: u16, password: &str) -> io::Result<Self> {
+        let stream = match time::timeout(
+            Self::CONNECT_TIMEOUT,
+            TcpStream::connect(format!("{}:{}", host, port)),
+        )
+        .await
+        {
+            Ok(res) => res?,
+            Err(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("Connecting to {}:{} timed out", host, port),
+                ))
+            }
+        };
+
+        Self::authenticate(Box::new(stream), password).await
+    }
+
+    async fn authenticate(stream: Box<dyn Transport>, password: &str) -> io::Result<Self> {
+        let mut client = Self { stream, next_id: 0 };
+
+        let id = client
+            .send(RconPacket::TYPE_LOGIN, password.to_string())
+            .await?;
+
+        // some servers send an empty command response before the actual auth response
+        loop {
+            let packet = client.read_packet().await?;
+
+            if packet.request_id == -1 {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "RCON authentication failed",
+                ));
+            }
+
+            if packet.request_id == id && packet.type_ == RconPacket::TYPE_COMMAND {
+                break;
+            }
+        }
+
+        Ok(client)
+    }
+
+    /// Runs a console command and returns its output.
+    pub async fn command(&mut self, command: &str) -> io::Result<String> {
+        if command.len() > RconPacket::MAX_PAYLOAD_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Command is longer than {} bytes",
+                    RconPacket::MAX_PAYLOAD_SIZE
+                ),
+            ));
+        }
+
+        let id = self
+            .send(RconPacket::TYPE_COMMAND, command.to_string())
+            .await?;
+
+        // long outputs arrive in several fragments and there is no end marker,
+        // so a request with an invalid type is sent afterwards. The server handles
+        // requests in order, its answer marks the end of the command output.
+        let sentinel = self.send(RconPacket::TYPE_RESPONSE, String::new()).await?;
+
+        let mut output = String::new();
+        loop {
+            let packet = self.read_packet().await?;
+
+            if packet.request_id == sentinel {
+                break;
+            }
+
+            if packet.request_id == id {
+                output.push_str(&packet.payload);
+            }
+        }
+
+        Ok(output)
+    }
+
+    async fn send(&mut self, type_: i32, payload: String) -> io::Result<i32> {
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        let id = self.next_id;
+
+        let bytes: Vec<u8> = RconPacket::new(id, type_, payload).into();
+        self.stream.write_all(bytes.as_slice()).await?;
+
+        Ok(id)
+    }
+
+    async fn read_packet(&mut self) -> io::Result<RconPacket> {
+        match time::timeout(Self::READ_TIMEOUT, self.read_packet_untimed()).await {
+            Ok(res) => res,
+            Err(_) => Err(LimitExceeded::ReadTimeout(Self::READ_TIMEOUT).into()),
+        }
+    }
+
+    async fn read_packet_untimed(&mut self) -> io::Result<RconPacket> {
+        let length = self.stream.read_i32_le().await?;
+
+        if length < RconPacket::HEADER_SIZE as i32
+            || length as usize > RconPacket::HEADER_SIZE + RconPacket::MAX_RESPONSE_PAYLOAD_SIZE
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid RCON packet length {}", length),
+            ));
+        }
+
+        let mut data = vec![0; length as usize];
+        self.stream.read_exact(&mut data).await?;
+
+        RconPacket::try_from(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{duplex, DuplexStream};
+
+    use super::*;
+
+    async fn read_request(server: &mut DuplexStream) -> RconPacket {
+        let length = server.read_i32_le().await.unwrap();
+        let mut data = vec![0; length as usize];
+        server.read_exact(&mut data).await.unwrap();
+
+        RconPacket::try_from(data).unwrap()
+    }
+
+    async fn respond(server: &mut DuplexStream, request_id: i32, type_: i32, payload: &str) {
+        let bytes: Vec<u8> = RconPacket::new(request_id, type_, payload.to_string()).into();
+        server.write_all(&bytes).await.unwrap();
+    }
+
+    /// Runs a command against a server splitting the output into fragments of 4096 chars,
+    /// like the notchian server does.
+    async fn run_command(output: String) -> io::Result<String> {
+        // a small buffer splits every packet into several reads
+        let (client_io, mut server_io) = duplex(64);
+
+        let server = async move {
+            let login = read_request(&mut server_io).await;
+            assert_eq!(login.type_, RconPacket::TYPE_LOGIN);
+            assert_eq!(login.payload, "secret");
+            respond(
+                &mut server_io,
+                login.request_id,
+                RconPacket::TYPE_COMMAND,
+                "",
+            )
+            .await;
+
+            let command = read_request(&mut server_io).await;
+            assert_eq!(command.payload, "list");
+            let sentinel = read_request(&mut server_io).await;
+
+            let chars: Vec<char> = output.chars().collect();
+            for fragment in chars.chunks(4096) {
+                let fragment: String = fragment.iter().collect();
+                respond(
+                    &mut server_io,
+                    command.request_id,
+                    RconPacket::TYPE_RESPONSE,
+                    &fragment,
+                )
+                .await;
+            }
+            respond(
+                &mut server_io,
+                sentinel.request_id,
+                RconPacket::TYPE_RESPONSE,
+                "Unknown request 0",
+            )
+            .await;
+        };
+
+        let client = async {
+            let mut client = RconClient::authenticate(Box::new(client_io), "secret").await?;
+            client.command("list").await
+        };
+
+        let (_, output) = tokio::join!(server, client);
+        output
+    }
+
+    #[tokio::test]
+    async fn reassembles_fragments() {
+        // two and a half fragments, the 4096 chars of a full one take 8192 bytes
+        let output: String = "é".repeat(4096 * 2 + 2048);
+        assert_eq!(run_command(output.clone()).await.unwrap(), output);
+
+        assert_eq!(run_command(String::new()).await.unwrap(), "");
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_password_and_oversized_packets() {
+        let (client_io, mut server_io) = duplex(1024);
+        let server = async move {
+            read_request(&mut server_io).await;
+            respond(&mut server_io, -1, RconPacket::TYPE_COMMAND, "").await;
+        };
+        let (_, res) = tokio::join!(
+            server,
+            RconClient::authenticate(Box::new(client_io), "wrong")
+        );
+        assert_eq!(
+            res.err().map(|e| e.kind()),
+            Some(io::ErrorKind::PermissionDenied)
+        );
+
+        let (client_io, mut server_io) = duplex(1024);
+        let server = async move {
+            read_request(&mut server_io).await;
+            let length =
+                (RconPacket::HEADER_SIZE + RconPacket::MAX_RESPONSE_PAYLOAD_SIZE + 1) as i32;
+            server_io.write_all(&length.to_le_bytes()).await.unwrap();
+        };
+        let (_, res) = tokio::join!(
+            server,
+            RconClient::authenticate(Box::new(client_io), "secret")
+        );
+        assert_eq!(
+            res.err().map(|e| e.kind()),
+            Some(io::ErrorKind::InvalidData)
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::env;
+
+use crate::minecraft::{text_component::TextComponent, translation::Language};
+
+use super::{spans, Color, Span};
+
+const RESET: &str = "\x1b[0m";
+
+// foreground codes of the named colors, in the order of the legacy codes
+const NAMED_SGR: [u8; 16] = [
+    30, 34, 32, 36, 31, 35, 33, 37, 90, 94, 92, 96, 91, 95, 93, 97,
+];
+
+// channel values of the 6x6x6 color cube in the 256 color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// How hex colors are written, named colors always use the 16 standard colors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorDepth {
+    #[default]
+    TrueColor,
+    Ansi256,
+}
+
+impl ColorDepth {
+    /// Truecolor if the terminal announces it in `COLORTERM`, 256 colors otherwise.
+    pub fn detect() -> Self {
+        match env::var("COLORTERM").as_deref() {
+            Ok("truecolor" | "24bit") => Self::TrueColor,
+            _ => Self::Ansi256,
+        }
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> i32 {
+    let square = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+    square(r1, r2) + square(g1, g2) + square(b1, b2)
+}
+
+/// The closest color of the 256 color palette, from the color cube or the grayscale ramp.
+pub fn ansi256(rgb: (u8, u8, u8)) -> u8 {
+    let level = |channel: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (channel as i32 - CUBE_LEVELS[i] as i32).abs())
+            .unwrap_or_default()
+    };
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = (CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+
+    // the ramp goes from 8 to 238 in steps of 10
+    let average = (rgb.0 as i32 + rgb.1 as i32 + rgb.2 as i32) / 3;
+    let step = ((average - 8 + 5) / 10).clamp(0, 23);
+    let gray = (8 + step * 10) as u8;
+
+    if distance(rgb, (gray, gray, gray)) < distance(rgb, cube) {
+        232 + step as u8
+    } else {
+        16 + 36 * r as u8 + 6 * g as u8 + b as u8
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AnsiRenderer {
+    depth: ColorDepth,
+    language: Language,
+}
+
+impl AnsiRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn color_depth(mut self, depth: ColorDepth) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    fn codes(&self, span: &Span) -> Vec<String> {
+        let mut codes = Vec::new();
+
+        let is_true = |arg: Option<bool>| arg.is_some_and(|x| x);
+        for (enabled, code) in [
+            (span.style.bold, "1"),
+            (span.style.italic, "3"),
+            (span.style.underlined, "4"),
+            (span.style.obfuscated, "5"), // blinking is the closest terminals get
+            (span.style.strikethrough, "9"),
+        ] {
+            if is_true(enabled) {
+                codes.push(code.to_string());
+            }
+        }
+
+        match span.color() {
+            Some(Color::Named(index)) => codes.push(NAMED_SGR[index].to_string()),
+            Some(Color::Rgb(r, g, b)) if self.depth == ColorDepth::TrueColor => {
+                codes.push(format!("38;2;{};{};{}", r, g, b))
+            }
+            Some(Color::Rgb(r, g, b)) => codes.push(format!("38;5;{}", ansi256((r, g, b)))),
+            None => {}
+        }
+
+        codes
+    }
+
+    /// Renders the component with SGR escape sequences, every style change starts with a reset.
+    pub fn render(&self, component: &TextComponent) -> String {
+        let mut rendered = String::new();
+        let mut is_styled = false;
+
+        for span in spans(&component.translated(self.language)) {
+            let codes = self.codes(&span);
+
+            if is_styled || !codes.is_empty() {
+                rendered.push_str("\x1b[0");
+                for code in &codes {
+                    rendered.push(';');
+                    rendered.push_str(code);
+                }
+                rendered.push('m');
+            }
+            is_styled = !codes.is_empty();
+
+            // server text must not smuggle in its own escape sequences
+            rendered.extend(span.text.chars().filter(|c| *c == '\n' || !c.is_control()));
+        }
+
+        if is_styled {
+            rendered.push_str(RESET);
+        }
+
+        rendered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inherits_styles() {
+        let component: TextComponent = serde_json::from_str(
+            r#"{"text": "a", "bold": true, "extra": [{"text": "b", "color": "red"}, {"text": "c", "bold": false}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            AnsiRenderer::new().render(&component),
+            "\x1b[0;1ma\x1b[0;1;91mb\x1b[0mc"
+        );
+    }
+
+    #[test]
+    fn hex_colors() {
+        let component: TextComponent =
+            serde_json::from_str(r##"{"text": "hex", "color": "#FF0000"}"##).unwrap();
+
+        assert_eq!(
+            AnsiRenderer::new().render(&component),
+            "\x1b[0;38;2;255;0;0mhex\x1b[0m"
+        );
+        assert_eq!(
+            AnsiRenderer::new()
+                .color_depth(ColorDepth::Ansi256)
+                .render(&component),
+            "\x1b[0;38;5;196mhex\x1b[0m"
+        );
+        assert_eq!(ansi256((0x80, 0x80, 0x80)), 244);
+    }
+
+    #[test]
+    fn strips_control_characters() {
+        let component = TextComponent::text("a\x1b]0;title\x07b\u{9b}2J\r\nc");
+
+        assert_eq!(AnsiRenderer::new().render(&component), "a]0;titleb2J\nc");
+    }
+}
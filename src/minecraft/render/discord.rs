@@ -0,0 +1,147 @@
+use crate::minecraft::{text_component::TextComponent, translation::Language};
+
+use super::{spans, Color, Span};
+
+// discord's ansi code blocks only support these foreground colors, with the rgb values of its theme
+const PALETTE: [(u8, (u8, u8, u8)); 8] = [
+    (30, (0x4F, 0x54, 0x5C)), // gray
+    (31, (0xDC, 0x32, 0x2F)), // red
+    (32, (0x85, 0x99, 0x00)), // green
+    (33, (0xB5, 0x89, 0x00)), // yellow
+    (34, (0x26, 0x8B, 0xD2)), // blue
+    (35, (0xD3, 0x36, 0x82)), // pink
+    (36, (0x2A, 0xA1, 0x98)), // cyan
+    (37, (0xFF, 0xFF, 0xFF)), // white
+];
+
+// closest palette entries of the named colors, in the order of the legacy codes
+const NAMED: [u8; 16] = [
+    30, 34, 32, 36, 31, 35, 33, 37, 30, 34, 32, 36, 31, 35, 33, 37,
+];
+
+/// The palette entry closest to a color, by the distance of their rgb values.
+pub fn nearest(color: Color) -> u8 {
+    let (r, g, b) = match color {
+        Color::Named(index) => return NAMED[index],
+        Color::Rgb(r, g, b) => (r, g, b),
+    };
+    let square = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+
+    PALETTE
+        .iter()
+        .min_by_key(|(_, (pr, pg, pb))| square(r, *pr) + square(g, *pg) + square(b, *pb))
+        .map(|(code, _)| *code)
+        .unwrap_or(37)
+}
+
+/// Renders components for discord's ```ansi code blocks,
+/// which know the eight palette colors, bold and underline.
+#[derive(Debug, Clone, Default)]
+pub struct DiscordAnsiRenderer {
+    language: Language,
+}
+
+impl DiscordAnsiRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    fn codes(span: &Span) -> Vec<u8> {
+        let is_true = |arg: Option<bool>| arg.is_some_and(|x| x);
+        let mut codes = Vec::new();
+
+        if is_true(span.style.bold) {
+            codes.push(1);
+        }
+        if is_true(span.style.underlined) {
+            codes.push(4);
+        }
+        if let Some(color) = span.color() {
+            codes.push(nearest(color));
+        }
+
+        codes
+    }
+
+    /// The escaped text only, without the surrounding code block.
+    pub fn render_text(&self, component: &TextComponent) -> String {
+        let mut rendered = String::new();
+        let mut is_styled = false;
+
+        for span in spans(&component.translated(self.language)) {
+            let codes = Self::codes(&span);
+
+            if is_styled || !codes.is_empty() {
+                rendered.push_str("\x1b[0");
+                for code in &codes {
+                    rendered.push_str(&format!(";{}", code));
+                }
+                rendered.push('m');
+            }
+            is_styled = !codes.is_empty();
+
+            // a zero width space after every backtick keeps text from closing the code block,
+            // also across spans, and escape sequences of the server are dropped
+            for c in span.text.chars() {
+                match c {
+                    '`' => rendered.push_str("`\u{200B}"),
+                    '\n' => rendered.push(c),
+                    c if c.is_control() => {}
+                    c => rendered.push(c),
+                }
+            }
+        }
+
+        if is_styled {
+            rendered.push_str("\x1b[0m");
+        }
+
+        rendered
+    }
+
+    pub fn render(&self, component: &TextComponent) -> String {
+        format!("```ansi\n{}\n```", self.render_text(component))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snaps_to_palette() {
+        assert_eq!(nearest(Color::Rgb(0xFF, 0x00, 0x00)), 31);
+        assert_eq!(nearest(Color::Rgb(0x10, 0x80, 0xFF)), 34);
+        assert_eq!(nearest(Color::Named(6)), 33);
+
+        let component: TextComponent = serde_json::from_str(
+            r##"["", {"text": "Hi", "color": "#22AA99", "bold": true, "italic": true}, " ```"]"##,
+        )
+        .unwrap();
+        assert_eq!(
+            DiscordAnsiRenderer::new().render(&component),
+            "```ansi\n\x1b[0;1;36mHi\x1b[0m `\u{200B}`\u{200B}`\u{200B}\n```"
+        );
+    }
+
+    #[test]
+    fn escapes_backticks_and_control_characters() {
+        let renderer = DiscordAnsiRenderer::new();
+
+        let component = TextComponent::text("````");
+        assert!(!renderer.render_text(&component).contains("``"));
+
+        // a fence split across spans
+        let component: TextComponent =
+            serde_json::from_str(r#"["", "a`", {"text": "``b", "bold": true}]"#).unwrap();
+        assert!(!renderer.render_text(&component).contains("``"));
+
+        let component = TextComponent::text("a\x1b[31mb\u{9b}c\r\nd");
+        assert_eq!(renderer.render_text(&component), "a[31mbc\nd");
+    }
+}
@@ -0,0 +1,98 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+pub const GLYPH_HEIGHT: u32 = 8;
+
+pub static FONT: LazyLock<Font> =
+    LazyLock::new(|| Font::parse(include_str!("../../../data/font/ascii.txt")));
+
+#[derive(Debug, Clone)]
+pub struct Glyph {
+    pub width: u32,
+    // one byte per row, the leftmost pixel is the highest bit
+    rows: [u8; GLYPH_HEIGHT as usize],
+}
+
+impl Glyph {
+    pub fn is_set(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < GLYPH_HEIGHT && self.rows[y as usize] & (0x80 >> x) != 0
+    }
+
+    /// The width including the spacing to the next glyph.
+    pub fn advance(&self) -> u32 {
+        self.width + 1
+    }
+}
+
+#[derive(Debug)]
+pub struct Font {
+    glyphs: HashMap<char, Glyph>,
+    missing: Glyph,
+}
+
+impl Font {
+    /// Parses the bundled font, see the header of `data/font/ascii.txt` for the format.
+    fn parse(data: &str) -> Self {
+        let mut glyphs = HashMap::new();
+
+        let mut lines = data
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty() && !line.starts_with("//"));
+
+        while let Some(header) = lines.next() {
+            let name = header
+                .strip_prefix("char ")
+                .unwrap_or_else(|| panic!("expected a glyph header, got {:?}", header));
+            let c = match name {
+                "space" => ' ',
+                "missing" => char::REPLACEMENT_CHARACTER,
+                _ => name.chars().next().unwrap(),
+            };
+
+            let mut glyph = Glyph {
+                width: 0,
+                rows: [0; GLYPH_HEIGHT as usize],
+            };
+            for row in glyph.rows.iter_mut() {
+                let pixels = lines
+                    .next()
+                    .unwrap_or_else(|| panic!("glyph {:?} is missing rows", c));
+                glyph.width = pixels.len() as u32;
+
+                for (x, pixel) in pixels.chars().enumerate() {
+                    if pixel == '#' {
+                        *row |= 0x80 >> x;
+                    }
+                }
+            }
+
+            glyphs.insert(c, glyph);
+        }
+
+        let missing = glyphs
+            .remove(&char::REPLACEMENT_CHARACTER)
+            .expect("the font has no glyph for missing characters");
+
+        Self { glyphs, missing }
+    }
+
+    pub fn glyph(&self, c: char) -> &Glyph {
+        self.glyphs.get(&c).unwrap_or(&self.missing)
+    }
+
+    /// Characters with glyphs of the given width, obfuscated text cycles through them.
+    pub fn chars_with_width(&self, width: u32) -> Vec<char> {
+        let mut chars: Vec<char> = self
+            .glyphs
+            .iter()
+            .filter(|(c, glyph)| glyph.width == width && **c != ' ')
+            .map(|(c, _)| *c)
+            .collect();
+        chars.sort_unstable();
+        chars
+    }
+
+    pub fn width(&self, text: &str) -> u32 {
+        text.chars().map(|c| self.glyph(c).advance()).sum()
+    }
+}
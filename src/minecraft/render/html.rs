@@ -0,0 +1,148 @@
+use crate::minecraft::{
+    text_component::{ClickAction, HoverEvent, TextComponent},
+    translation::Language,
+};
+
+use super::{spans, Span};
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '\n' => escaped.push_str("<br>"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HtmlRenderer {
+    language: Language,
+}
+
+impl HtmlRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    fn css(span: &Span) -> String {
+        let style = &span.style;
+        let is_true = |arg: Option<bool>| arg.is_some_and(|x| x);
+        let mut css = Vec::new();
+
+        if let Some((r, g, b)) = span.color().map(|color| color.rgb()) {
+            css.push(format!("color:#{:02x}{:02x}{:02x}", r, g, b));
+        }
+        if is_true(style.bold) {
+            css.push("font-weight:bold".to_string());
+        }
+        if is_true(style.italic) {
+            css.push("font-style:italic".to_string());
+        }
+
+        let decorations: Vec<&str> = [
+            (style.underlined, "underline"),
+            (style.strikethrough, "line-through"),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| is_true(*enabled))
+        .map(|(_, decoration)| decoration)
+        .collect();
+        if !decorations.is_empty() {
+            css.push(format!("text-decoration:{}", decorations.join(" ")));
+        }
+
+        // there's no way to scramble text with css, it's blurred like a spoiler instead
+        if is_true(style.obfuscated) {
+            css.push("filter:blur(0.25em)".to_string());
+        }
+
+        css.join(";")
+    }
+
+    fn render_span(&self, span: &Span) -> String {
+        let css = Self::css(span);
+
+        let title = match span.style.hover_event.as_deref() {
+            Some(HoverEvent::ShowText(text)) => {
+                let plain: String = spans(&text.translated(self.language))
+                    .into_iter()
+                    .map(|span| span.text)
+                    .collect();
+                Some(plain)
+            }
+            _ => None,
+        };
+
+        let mut attributes = String::new();
+        if !css.is_empty() {
+            attributes.push_str(&format!(" style=\"{}\"", css));
+        }
+        if let Some(title) = title {
+            let title = escape(&title).replace("<br>", "&#10;");
+            attributes.push_str(&format!(" title=\"{}\"", title));
+        }
+
+        let mut html = match attributes.is_empty() {
+            true => escape(&span.text),
+            false => format!("<span{}>{}</span>", attributes, escape(&span.text)),
+        };
+
+        // only links to websites, anything else could run scripts
+        if let Some(click) = span.style.click_event.as_deref() {
+            let is_web = click.value.starts_with("https://") || click.value.starts_with("http://");
+            if click.action == ClickAction::OpenUrl && is_web {
+                html = format!(
+                    "<a href=\"{}\" rel=\"nofollow noopener\">{}</a>",
+                    escape(&click.value),
+                    html
+                );
+            }
+        }
+
+        html
+    }
+
+    /// Renders the component as inline html, styled with inline css.
+    pub fn render(&self, component: &TextComponent) -> String {
+        spans(&component.translated(self.language))
+            .iter()
+            .map(|span| self.render_span(span))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn styles_and_escaping() {
+        let component: TextComponent = serde_json::from_str(
+            r#"["<b>", {"text": "Shop & more", "color": "gold", "bold": true, "underlined": true, "strikethrough": true,
+                "clickEvent": {"action": "open_url", "value": "https://example.com/?a=1&b=2"},
+                "hoverEvent": {"action": "show_text", "contents": "Open \"shop\""}}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            HtmlRenderer::new().render(&component),
+            "&lt;b&gt;<a href=\"https://example.com/?a=1&amp;b=2\" rel=\"nofollow noopener\">\
+             <span style=\"color:#ffaa00;font-weight:bold;text-decoration:underline line-through\" \
+             title=\"Open &quot;shop&quot;\">Shop &amp; more</span></a>"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use crate::minecraft::{
+    text_component::{Style, TextComponent},
+    translation::Language,
+};
+
+use super::spans;
+
+// characters with a meaning in discord's markdown, including masked links and headings
+const SPECIAL: [char; 13] = [
+    '\\', '*', '_', '~', '`', '|', '>', '#', '-', '[', ']', '(', ')',
+];
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if SPECIAL.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// The parts of a style markdown can show, colors are lost.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Formatting {
+    bold: bool,
+    italic: bool,
+    underlined: bool,
+    strikethrough: bool,
+    obfuscated: bool,
+}
+
+impl Formatting {
+    fn of(style: &Style) -> Self {
+        let is_true = |arg: Option<bool>| arg.is_some_and(|x| x);
+
+        Self {
+            bold: is_true(style.bold),
+            italic: is_true(style.italic),
+            underlined: is_true(style.underlined),
+            strikethrough: is_true(style.strikethrough),
+            obfuscated: is_true(style.obfuscated),
+        }
+    }
+
+    /// Markers from the outside in, obfuscated text is hidden in a spoiler.
+    fn markers(&self) -> Vec<&'static str> {
+        let mut markers = Vec::new();
+
+        if self.obfuscated {
+            markers.push("||");
+        }
+        if self.strikethrough {
+            markers.push("~~");
+        }
+        if self.underlined {
+            markers.push("__");
+        }
+        match (self.bold, self.italic) {
+            (true, true) => markers.push("***"),
+            (true, false) => markers.push("**"),
+            (false, true) => markers.push("*"),
+            (false, false) => {}
+        }
+
+        markers
+    }
+}
+
+type Run = (Formatting, String);
+
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownRenderer {
+    language: Language,
+    max_length: Option<usize>,
+}
+
+impl MarkdownRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Caps the rendered length in chars, e.g. to the 1024 of an embed field.
+    /// Longer text is cut off with an ellipsis, without breaking escapes or markers.
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Renders the component with discord flavored markdown, the server text is escaped.
+    pub fn render(&self, component: &TextComponent) -> String {
+        // spans that only differ in their color look the same here
+        let mut runs: Vec<Run> = Vec::new();
+        for span in spans(&component.translated(self.language)) {
+            let formatting = Formatting::of(&span.style);
+            match runs.last_mut() {
+                Some((last, text)) if *last == formatting => text.push_str(&span.text),
+                _ => runs.push((formatting, span.text)),
+            }
+        }
+
+        let rendered = render_runs(&runs);
+        match self.max_length {
+            Some(max_length) if rendered.chars().count() > max_length => {
+                truncate(&runs, max_length)
+            }
+            _ => rendered,
+        }
+    }
+}
+
+fn render_runs(runs: &[Run]) -> String {
+    let mut rendered = String::new();
+    let mut was_wrapped = false;
+
+    for (formatting, text) in runs {
+        let markers = formatting.markers();
+
+        // markers only work on lines, without whitespace at the inner side
+        for (index, line) in text.split('\n').enumerate() {
+            if index > 0 {
+                rendered.push('\n');
+                was_wrapped = false;
+            }
+
+            let content = line.trim();
+            if markers.is_empty() || content.is_empty() {
+                rendered.push_str(&escape(line));
+                was_wrapped = was_wrapped && line.is_empty();
+                continue;
+            }
+
+            let leading = &line[..line.len() - line.trim_start().len()];
+            let trailing = &line[line.trim_end().len()..];
+
+            // a zero width space keeps the markers of neighbours apart
+            if was_wrapped && leading.is_empty() {
+                rendered.push('\u{200B}');
+            }
+
+            rendered.push_str(leading);
+            for marker in &markers {
+                rendered.push_str(marker);
+            }
+            rendered.push_str(&escape(content));
+            for marker in markers.iter().rev() {
+                rendered.push_str(marker);
+            }
+            rendered.push_str(trailing);
+
+            was_wrapped = trailing.is_empty();
+        }
+    }
+
+    rendered
+}
+
+// the rendered length grows unevenly with escapes and markers,
+// so the longest text that still fits is bisected on the runs
+fn truncate(runs: &[Run], max_length: usize) -> String {
+    let cut = |keep: usize| {
+        let mut left = keep;
+        let mut cut = Vec::new();
+        for (formatting, text) in runs {
+            let kept: String = text.chars().take(left).collect();
+            left -= kept.chars().count();
+            cut.push((*formatting, kept));
+            if left == 0 {
+                break;
+            }
+        }
+        if let Some((_, text)) = cut.last_mut() {
+            text.push('…');
+        }
+        render_runs(&cut)
+    };
+
+    let (mut fits, mut too_long) = (0, runs.iter().map(|(_, t)| t.chars().count()).sum());
+    while too_long - fits > 1 {
+        let keep = (fits + too_long) / 2;
+        match cut(keep).chars().count() <= max_length {
+            true => fits = keep,
+            false => too_long = keep,
+        }
+    }
+
+    cut(fits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(json: &str) -> String {
+        MarkdownRenderer::new().render(&serde_json::from_str(json).unwrap())
+    }
+
+    #[test]
+    fn inherits_and_merges() {
+        assert_eq!(
+            render(
+                r#"{"text": "", "bold": true, "extra": [
+                    {"text": "Sky", "color": "aqua"}, {"text": "block ", "color": "white"},
+                    {"text": "Network", "italic": true}, {"text": " plain", "bold": false}]}"#
+            ),
+            "**Skyblock** ***Network*** plain"
+        );
+    }
+
+    #[test]
+    fn escapes_server_text() {
+        assert_eq!(
+            render(r#""*free* [nitro](https://example.com) __now__""#),
+            "\\*free\\* \\[nitro\\]\\(https://example.com\\) \\_\\_now\\_\\_"
+        );
+    }
+
+    #[test]
+    fn obfuscated_as_spoiler() {
+        assert_eq!(
+            render(
+                r#"["", {"text": " secret ", "obfuscated": true}, "\n", {"text": "line", "underlined": true}]"#
+            ),
+            " ||secret|| \n__line__"
+        );
+    }
+
+    #[test]
+    fn truncates_without_breaking_markup() {
+        let component: TextComponent = serde_json::from_str(
+            r#"["", {"text": "**********", "bold": true}, {"text": "__________", "obfuscated": true}]"#,
+        )
+        .unwrap();
+
+        for max_length in 5..49 {
+            let rendered = MarkdownRenderer::new()
+                .max_length(max_length)
+                .render(&component);
+
+            assert!(rendered.chars().count() <= max_length, "{}", rendered);
+            assert!(rendered.ends_with("…**") || rendered.ends_with("…||"));
+            assert!(!rendered.contains("\\…"));
+        }
+
+        assert_eq!(
+            MarkdownRenderer::new().max_length(12).render(&component),
+            "**\\*\\*\\*…**"
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use super::{
+    legacy_format::COLORS,
+    text_component::{Style, TextComponent},
+};
+
+pub mod ansi;
+pub mod discord;
+pub mod font;
+pub mod html;
+pub mod markdown;
+pub mod png;
+
+// rgb values of the named colors, in the order of the legacy codes
+const NAMED_RGB: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00),
+    (0x00, 0x00, 0xAA),
+    (0x00, 0xAA, 0x00),
+    (0x00, 0xAA, 0xAA),
+    (0xAA, 0x00, 0x00),
+    (0xAA, 0x00, 0xAA),
+    (0xFF, 0xAA, 0x00),
+    (0xAA, 0xAA, 0xAA),
+    (0x55, 0x55, 0x55),
+    (0x55, 0x55, 0xFF),
+    (0x55, 0xFF, 0x55),
+    (0x55, 0xFF, 0xFF),
+    (0xFF, 0x55, 0x55),
+    (0xFF, 0x55, 0xFF),
+    (0xFF, 0xFF, 0x55),
+    (0xFF, 0xFF, 0xFF),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    /// Index of a named color in the order of the legacy codes
+    Named(usize),
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    pub fn parse(color: &str) -> Option<Self> {
+        if let Some(index) = COLORS.iter().position(|(_, name)| *name == color) {
+            return Some(Self::Named(index));
+        }
+
+        let hex = color.strip_prefix('#')?;
+        if hex.len() != 6 {
+            return None;
+        }
+        let rgb = u32::from_str_radix(hex, 16).ok()?;
+
+        Some(Self::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+    }
+
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        match *self {
+            Self::Named(index) => NAMED_RGB[index],
+            Self::Rgb(r, g, b) => (r, g, b),
+        }
+    }
+}
+
+/// A run of text with the style it's shown with, including the inherited properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+impl Span {
+    pub fn color(&self) -> Option<Color> {
+        self.style.color.as_deref().and_then(Color::parse)
+    }
+}
+
+/// Flattens a component tree into spans, merging neighbours with the same style.
+/// Translations are not resolved, see [`TextComponent::translated`].
+pub fn spans(component: &TextComponent) -> Vec<Span> {
+    let mut spans = Vec::new();
+    push_spans(component, &Style::default(), &mut spans);
+    spans
+}
+
+fn push_spans(component: &TextComponent, parent: &Style, spans: &mut Vec<Span>) {
+    let style = component.style.inherit(parent);
+
+    let text = component.content_text();
+    if !text.is_empty() {
+        match spans.last_mut() {
+            Some(last) if last.style == style => last.text.push_str(&text),
+            _ => spans.push(Span {
+                text,
+                style: style.clone(),
+            }),
+        }
+    }
+
+    for child in &component.extra {
+        push_spans(child, &style, spans);
+    }
+}
@@ -0,0 +1,385 @@
+use std::{
+    io::{self, Cursor},
+    time::Duration,
+};
+
+use image::{imageops, DynamicImage, ImageOutputFormat, Rgba, RgbaImage};
+use rand::seq::SliceRandom;
+
+use crate::minecraft::{
+    text_component::{Style, TextComponent},
+    translation::Language,
+};
+
+use super::{
+    font::{Glyph, FONT, GLYPH_HEIGHT},
+    spans, Color,
+};
+
+type Rgb = (u8, u8, u8);
+
+// layout of a server list entry in unscaled pixels, like in the vanilla multiplayer screen
+const WIDTH: u32 = 305;
+const HEIGHT: u32 = 36;
+const ICON_SIZE: u32 = 32;
+const PADDING: i32 = 2;
+const TEXT_X: i32 = PADDING + ICON_SIZE as i32 + 3;
+const NAME_Y: i32 = 3;
+const MOTD_Y: i32 = 14;
+const LINE_HEIGHT: i32 = 9;
+const MOTD_LINES: usize = 2;
+const BARS_WIDTH: u32 = 10;
+const BARS_X: i32 = WIDTH as i32 - PADDING - BARS_WIDTH as i32;
+
+const BACKGROUND: Rgb = (0x1A, 0x1A, 0x1A);
+const NAME_COLOR: Rgb = (0xFF, 0xFF, 0xFF);
+const MOTD_COLOR: Rgb = (0x80, 0x80, 0x80);
+const GRAY: Rgb = (0xAA, 0xAA, 0xAA);
+const DARK_GRAY: Rgb = (0x55, 0x55, 0x55);
+const BAR_COLOR: Rgb = (0x00, 0xD8, 0x00);
+const EMPTY_BAR_COLOR: Rgb = (0x3F, 0x3F, 0x3F);
+
+/// The parts of the server list entry, the motd is drawn on up to two wrapped lines.
+#[derive(Debug, Clone, Default)]
+pub struct ServerListEntry {
+    pub name: String,
+    pub motd: TextComponent,
+    pub online: i32,
+    pub max: i32,
+    pub latency: Option<Duration>,
+    pub favicon: Option<DynamicImage>,
+}
+
+struct Canvas {
+    image: RgbaImage,
+    scale: u32,
+}
+
+impl Canvas {
+    fn new(scale: u32) -> Self {
+        let (r, g, b) = BACKGROUND;
+        Self {
+            image: RgbaImage::from_pixel(WIDTH * scale, HEIGHT * scale, Rgba([r, g, b, 0xFF])),
+            scale,
+        }
+    }
+
+    fn fill(&mut self, x: i32, y: i32, width: u32, height: u32, (r, g, b): Rgb) {
+        for px in x.max(0)..(x + width as i32).min(WIDTH as i32) {
+            for py in y.max(0)..(y + height as i32).min(HEIGHT as i32) {
+                for sx in 0..self.scale {
+                    for sy in 0..self.scale {
+                        self.image.put_pixel(
+                            px as u32 * self.scale + sx,
+                            py as u32 * self.scale + sy,
+                            Rgba([r, g, b, 0xFF]),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn draw_glyph(&mut self, x: i32, y: i32, glyph: &Glyph, italic: bool, color: Rgb) {
+        for row in 0..GLYPH_HEIGHT {
+            // the upper half leans one pixel to the right
+            let offset = (italic && row < GLYPH_HEIGHT / 2) as i32;
+            for column in 0..glyph.width {
+                if glyph.is_set(column, row) {
+                    self.fill(x + column as i32 + offset, y + row as i32, 1, 1, color);
+                }
+            }
+        }
+    }
+
+    /// Draws text with the vanilla drop shadow.
+    fn draw_text(&mut self, x: i32, y: i32, text: &[(char, Style)], default_color: Rgb) {
+        let is_true = |arg: Option<bool>| arg.is_some_and(|x| x);
+
+        // picked once so obfuscated text and its shadow match
+        let glyphs: Vec<Glyph> = text.iter().map(|(c, style)| glyph(*c, style)).collect();
+
+        // the shadow is drawn first so it never covers the text
+        for shadow in [true, false] {
+            let mut cursor = x;
+
+            for ((c, style), glyph) in text.iter().zip(&glyphs) {
+                let bold = is_true(style.bold);
+                let advance = advance(*c, style) as i32;
+
+                let (r, g, b) = style
+                    .color
+                    .as_deref()
+                    .and_then(Color::parse)
+                    .map(|color| color.rgb())
+                    .unwrap_or(default_color);
+                let (color, offset) = match shadow {
+                    true => ((r / 4, g / 4, b / 4), 1),
+                    false => ((r, g, b), 0),
+                };
+                let (gx, gy) = (cursor + offset, y + offset);
+
+                self.draw_glyph(gx, gy, glyph, is_true(style.italic), color);
+                if bold {
+                    self.draw_glyph(gx + 1, gy, glyph, is_true(style.italic), color);
+                }
+                if is_true(style.underlined) {
+                    self.fill(
+                        gx - 1,
+                        gy + GLYPH_HEIGHT as i32,
+                        advance as u32 + 1,
+                        1,
+                        color,
+                    );
+                }
+                if is_true(style.strikethrough) {
+                    self.fill(gx - 1, gy + 3, advance as u32 + 1, 1, color);
+                }
+
+                cursor += advance;
+            }
+        }
+    }
+
+    fn draw_icon(&mut self, favicon: Option<&DynamicImage>) {
+        let Some(favicon) = favicon else {
+            // vanilla shows the default pack icon, a question mark will do here
+            self.fill(PADDING, PADDING, ICON_SIZE, ICON_SIZE, DARK_GRAY);
+            let question_mark = [('?', Style::default())];
+            let width = text_width(&question_mark) as i32;
+            self.draw_text(
+                PADDING + (ICON_SIZE as i32 - width) / 2 + 1,
+                PADDING + (ICON_SIZE - GLYPH_HEIGHT) as i32 / 2,
+                &question_mark,
+                GRAY,
+            );
+            return;
+        };
+
+        let size = ICON_SIZE * self.scale;
+        let icon = favicon.resize_exact(size, size, imageops::FilterType::Triangle);
+        let offset = (PADDING as u32 * self.scale) as i64;
+        imageops::overlay(&mut self.image, &icon.to_rgba8(), offset, offset);
+    }
+
+    fn draw_bars(&mut self, latency: Option<Duration>) {
+        // same thresholds as the vanilla client
+        let bars = match latency.map(|l| l.as_millis()) {
+            None => 0,
+            Some(0..=149) => 5,
+            Some(150..=299) => 4,
+            Some(300..=599) => 3,
+            Some(600..=999) => 2,
+            Some(_) => 1,
+        };
+
+        for bar in 0..5 {
+            let height = bar as u32 + 2;
+            let color = match bar < bars {
+                true => BAR_COLOR,
+                false => EMPTY_BAR_COLOR,
+            };
+            self.fill(
+                BARS_X + bar * 2,
+                NAME_Y + 7 - height as i32,
+                1,
+                height,
+                color,
+            );
+        }
+    }
+}
+
+fn glyph(c: char, style: &Style) -> Glyph {
+    let glyph = FONT.glyph(c);
+
+    if style.obfuscated.is_some_and(|x| x) && c != ' ' {
+        // random characters of the same width, like the client does every frame
+        let candidates = FONT.chars_with_width(glyph.width);
+        if let Some(random) = candidates.choose(&mut rand::thread_rng()) {
+            return FONT.glyph(*random).clone();
+        }
+    }
+
+    glyph.clone()
+}
+
+fn advance(c: char, style: &Style) -> u32 {
+    FONT.glyph(c).advance() + style.bold.is_some_and(|x| x) as u32
+}
+
+fn text_width(text: &[(char, Style)]) -> u32 {
+    text.iter().map(|(c, style)| advance(*c, style)).sum()
+}
+
+/// Splits the text into lines at newlines and wraps them at spaces to fit the width.
+fn wrap(text: Vec<(char, Style)>, max_width: u32) -> Vec<Vec<(char, Style)>> {
+    let mut lines = vec![Vec::new()];
+    let mut width = 0;
+
+    for (c, style) in text {
+        if c == '\n' {
+            lines.push(Vec::new());
+            width = 0;
+            continue;
+        }
+
+        let advance = advance(c, &style);
+        let line = lines.last_mut().unwrap();
+
+        if width + advance > max_width && !line.is_empty() {
+            let split = line
+                .iter()
+                .rposition(|(c, _)| *c == ' ')
+                .map(|space| space + 1)
+                .unwrap_or(line.len());
+            let rest = line.split_off(split);
+
+            width = text_width(&rest);
+            lines.push(rest);
+        }
+
+        width += advance;
+        lines.last_mut().unwrap().push((c, style));
+    }
+
+    lines
+}
+
+#[derive(Debug, Clone)]
+pub struct PngRenderer {
+    scale: u32,
+    language: Language,
+}
+
+impl Default for PngRenderer {
+    fn default() -> Self {
+        Self {
+            scale: 2,
+            language: Language::default(),
+        }
+    }
+}
+
+impl PngRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Size of a font pixel in the image, the entry is 305x36 pixels unscaled.
+    pub fn scale(mut self, scale: u32) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    pub fn render_image(&self, entry: &ServerListEntry) -> RgbaImage {
+        let mut canvas = Canvas::new(self.scale);
+
+        canvas.draw_icon(entry.favicon.as_ref());
+
+        let name: Vec<(char, Style)> = entry.name.chars().map(|c| (c, Style::default())).collect();
+        canvas.draw_text(TEXT_X, NAME_Y, &name, NAME_COLOR);
+
+        canvas.draw_bars(entry.latency);
+
+        let gray = Style {
+            color: Some("gray".into()),
+            ..Default::default()
+        };
+        let dark_gray = Style {
+            color: Some("dark_gray".into()),
+            ..Default::default()
+        };
+        let players: Vec<(char, Style)> = entry
+            .online
+            .to_string()
+            .chars()
+            .map(|c| (c, gray.clone()))
+            .chain([('/', dark_gray)])
+            .chain(entry.max.to_string().chars().map(|c| (c, gray.clone())))
+            .collect();
+        let players_x = BARS_X - 3 - text_width(&players) as i32;
+        canvas.draw_text(players_x, NAME_Y, &players, MOTD_COLOR);
+
+        let motd: Vec<(char, Style)> = spans(&entry.motd.translated(self.language))
+            .into_iter()
+            .flat_map(|span| {
+                let style = span.style;
+                span.text
+                    .chars()
+                    .map(move |c| (c, style.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        let max_width = (WIDTH as i32 - TEXT_X - PADDING) as u32;
+
+        for (index, line) in wrap(motd, max_width)
+            .into_iter()
+            .take(MOTD_LINES)
+            .enumerate()
+        {
+            canvas.draw_text(
+                TEXT_X,
+                MOTD_Y + index as i32 * LINE_HEIGHT,
+                &line,
+                MOTD_COLOR,
+            );
+        }
+
+        canvas.image
+    }
+
+    pub fn render(&self, entry: &ServerListEntry) -> io::Result<Vec<u8>> {
+        let mut png = Vec::new();
+        DynamicImage::ImageRgba8(self.render_image(entry))
+            .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+            .map_err(io::Error::other)?;
+        Ok(png)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_at_spaces() {
+        let text = |s: &str| s.chars().map(|c| (c, Style::default())).collect::<Vec<_>>();
+        let line = |l: &Vec<(char, Style)>| l.iter().map(|(c, _)| *c).collect::<String>();
+
+        // "aaaa " is 5 * 6 pixels wide
+        let lines = wrap(text("aaaa bbbb\ncc"), 40);
+        assert_eq!(
+            lines.iter().map(line).collect::<Vec<_>>(),
+            vec!["aaaa ", "bbbb", "cc"]
+        );
+    }
+
+    #[test]
+    fn draws_colored_motd() {
+        let entry = ServerListEntry {
+            name: "A Minecraft Server".into(),
+            motd: serde_json::from_str(r#"{"text": "I", "color": "red"}"#).unwrap(),
+            online: 1,
+            max: 20,
+            ..Default::default()
+        };
+        let image = PngRenderer::new().scale(1).render_image(&entry);
+
+        assert_eq!(image.dimensions(), (WIDTH, HEIGHT));
+        // top left pixel of the I and its shadow
+        assert_eq!(
+            image.get_pixel(TEXT_X as u32, MOTD_Y as u32),
+            &Rgba([0xFF, 0x55, 0x55, 0xFF])
+        );
+        assert_eq!(
+            image.get_pixel(TEXT_X as u32 + 3, MOTD_Y as u32 + 1),
+            &Rgba([0x3F, 0x15, 0x15, 0xFF])
+        );
+    }
+}
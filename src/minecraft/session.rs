@@ -0,0 +1,87 @@
+use std::io;
+
+use serde::Serialize;
+use serenity::async_trait;
+use uuid::Uuid;
+
+/// A premium account, the access token is obtained through the Microsoft login.
+#[derive(Debug, Clone)]
+pub struct Account {
+    pub name: String,
+    pub uuid: Uuid,
+    pub access_token: String,
+}
+
+/// Tells the session server which server the account is joining, so the server
+/// can verify the player. Implemented by [`MojangSessionService`].
+#[async_trait]
+pub trait SessionService: Send + Sync {
+    async fn join(&self, account: &Account, server_hash: &str) -> io::Result<()>;
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JoinRequest<'a> {
+    access_token: &'a str,
+    selected_profile: String,
+    server_id: &'a str,
+}
+
+pub struct MojangSessionService {
+    http: reqwest::Client,
+    url: String,
+}
+
+impl MojangSessionService {
+    pub const URL: &'static str = "https://sessionserver.mojang.com/session/minecraft/join";
+
+    pub fn new() -> Self {
+        Self::with_url(Self::URL.into())
+    }
+
+    pub fn with_url(url: String) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url,
+        }
+    }
+}
+
+impl Default for MojangSessionService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SessionService for MojangSessionService {
+    async fn join(&self, account: &Account, server_hash: &str) -> io::Result<()> {
+        let request = JoinRequest {
+            access_token: &account.access_token,
+            selected_profile: account.uuid.simple().to_string(),
+            server_id: server_hash,
+        };
+
+        let response = self
+            .http
+            .post(&self.url)
+            .json(&request)
+            .send()
+            .await
+            .map_err(io::Error::other)?;
+
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            reqwest::StatusCode::FORBIDDEN | reqwest::StatusCode::UNAUTHORIZED => {
+                Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "Session server rejected the access token",
+                ))
+            }
+            status => Err(io::Error::other(format!(
+                "Session server responded with {}",
+                status
+            ))),
+        }
+    }
+}
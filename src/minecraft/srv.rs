@@ -0,0 +1,294 @@
+use std::{
+    io,
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use hickory_resolver::{
+    config::{NameServerConfigGroup, ResolverConfig, ResolverOpts},
+    error::ResolveErrorKind,
+    proto::rr::rdata::SRV,
+    TokioAsyncResolver,
+};
+use rand::Rng;
+
+/// The port a notchian client assumes when none is given, SRV records are only consulted for it.
+pub const DEFAULT_PORT: u16 = 25565;
+
+const SERVICE_PREFIX: &str = "_minecraft._tcp.";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SrvTarget {
+    pub host: String,
+    pub port: u16,
+}
+
+#[derive(Debug)]
+pub struct Resolved {
+    pub addrs: Vec<SocketAddr>,
+    pub srv_target: Option<SrvTarget>,
+}
+
+pub struct SrvResolver {
+    resolver: TokioAsyncResolver,
+}
+
+impl SrvResolver {
+    pub fn from_system_conf() -> io::Result<Self> {
+        let resolver = TokioAsyncResolver::tokio_from_system_conf()?;
+
+        Ok(Self { resolver })
+    }
+
+    /// Uses a single plain UDP/TCP nameserver instead of the system configuration,
+    /// e.g. a local stub resolver.
+    pub fn with_nameserver(addr: SocketAddr) -> Self {
+        Self::with_nameserver_timeout(addr, ResolverOpts::default().timeout)
+    }
+
+    /// Like [`Self::with_nameserver`], giving up after a single attempt of the given length.
+    pub fn with_nameserver_timeout(addr: SocketAddr, timeout: Duration) -> Self {
+        let config = ResolverConfig::from_parts(
+            None,
+            vec![],
+            NameServerConfigGroup::from_ips_clear(&[addr.ip()], addr.port(), true),
+        );
+
+        let mut opts = ResolverOpts::default();
+        opts.timeout = timeout;
+        opts.attempts = 1;
+
+        Self {
+            resolver: TokioAsyncResolver::tokio(config, opts),
+        }
+    }
+
+    /// Looks up the `_minecraft._tcp` SRV records of a host.
+    /// The targets are ordered by priority, records sharing a priority are ordered by a weighted shuffle (RFC 2782).
+    pub async fn lookup_srv(&self, host: &str) -> io::Result<Vec<SrvTarget>> {
+        let lookup = match self
+            .resolver
+            .srv_lookup(format!("{}{}.", SERVICE_PREFIX, host.trim_end_matches('.')))
+            .await
+        {
+            Ok(lookup) => lookup,
+            Err(e) => {
+                return match e.kind() {
+                    ResolveErrorKind::NoRecordsFound { .. } => Ok(vec![]),
+                    _ => Err(e.into()),
+                }
+            }
+        };
+
+        // a target of "." means the service is decidedly not available at this domain
+        let mut records: Vec<SRV> = lookup
+            .iter()
+            .filter(|r| !r.target().is_root())
+            .cloned()
+            .collect();
+        records.sort_by_key(|r| r.priority());
+
+        let mut rng = rand::thread_rng();
+        let mut targets = Vec::with_capacity(records.len());
+
+        for priority_group in records.chunk_by(|a, b| a.priority() == b.priority()) {
+            let mut group = priority_group.to_vec();
+            group.sort_by_key(|r| r.weight() != 0); // zero weight records go first
+
+            while !group.is_empty() {
+                let total: u32 = group.iter().map(|r| r.weight() as u32).sum();
+                let pick = rng.gen_range(0..=total);
+
+                let mut running = 0;
+                let index = group
+                    .iter()
+                    .position(|r| {
+                        running += r.weight() as u32;
+                        running >= pick
+                    })
+                    .unwrap_or(0);
+
+                let record = group.remove(index);
+                targets.push(SrvTarget {
+                    host: record.target().to_utf8().trim_end_matches('.').to_string(),
+                    port: record.port(),
+                });
+            }
+        }
+
+        log::debug!("SRV targets for {}: {:?}", host, targets);
+
+        Ok(targets)
+    }
+
+    fn uses_srv(host: &str, port: u16) -> bool {
+        port == DEFAULT_PORT && host.parse::<IpAddr>().is_err()
+    }
+
+    /// Returns the preferred SRV target without resolving its addresses,
+    /// `None` if there is none or SRV records don't apply to the host and port.
+    pub async fn srv_target(&self, host: &str, port: u16) -> io::Result<Option<SrvTarget>> {
+        if !Self::uses_srv(host, port) {
+            return Ok(None);
+        }
+
+        Ok(self.lookup_srv_or_empty(host).await.into_iter().next())
+    }
+
+    // like the notchian client, a failed SRV lookup (SERVFAIL, REFUSED, timeout) isn't fatal
+    async fn lookup_srv_or_empty(&self, host: &str) -> Vec<SrvTarget> {
+        match self.lookup_srv(host).await {
+            Ok(targets) => targets,
+            Err(e) => {
+                log::debug!("SRV lookup of {} failed, using A/AAAA: {}", host, e);
+                vec![]
+            }
+        }
+    }
+
+    /// Resolves a host to socket addresses, preferring SRV records and falling back to A/AAAA.
+    pub async fn resolve(&self, host: &str, port: u16) -> io::Result<Resolved> {
+        if Self::uses_srv(host, port) {
+            for target in self.lookup_srv_or_empty(host).await {
+                match self.lookup_addrs(&target.host, target.port).await {
+                    Ok(addrs) if !addrs.is_empty() => {
+                        return Ok(Resolved {
+                            addrs,
+                            srv_target: Some(target),
+                        })
+                    }
+                    Ok(_) => continue,
+                    Err(e) => {
+                        log::debug!("SRV target {}:{} failed: {}", target.host, target.port, e)
+                    }
+                }
+            }
+        }
+
+        let addrs = self.lookup_addrs(host, port).await?;
+        if addrs.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "Hostname doesn't resolve to address",
+            ));
+        }
+
+        Ok(Resolved {
+            addrs,
+            srv_target: None,
+        })
+    }
+
+    async fn lookup_addrs(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        let lookup = self.resolver.lookup_ip(host).await?;
+
+        Ok(lookup.iter().map(|ip| SocketAddr::new(ip, port)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::Ipv4Addr, str::FromStr};
+
+    use hickory_resolver::proto::{
+        op::{Message, MessageType},
+        rr::{rdata::A, Name, RData, Record, RecordType},
+    };
+    use tokio::net::UdpSocket;
+
+    use super::*;
+
+    fn srv(priority: u16, weight: u16, port: u16, target: &str) -> RData {
+        RData::SRV(SRV::new(
+            priority,
+            weight,
+            port,
+            Name::from_str(target).unwrap(),
+        ))
+    }
+
+    /// Answers queries for `_minecraft._tcp.<name>` and A queries for the `*.test.` names,
+    /// the SRV lookup of `timeout.test.` is never answered.
+    async fn stub_resolver() -> SrvResolver {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let mut buf = [0; 512];
+            while let Ok((len, peer)) = socket.recv_from(&mut buf).await {
+                let request = Message::from_vec(&buf[..len]).unwrap();
+                let query = request.queries()[0].clone();
+                let name = query.name().to_utf8();
+
+                let mut response = Message::new();
+                response
+                    .set_id(request.id())
+                    .set_message_type(MessageType::Response)
+                    .set_recursion_desired(true)
+                    .set_recursion_available(true)
+                    .add_query(query.clone());
+
+                let answers = match (query.query_type(), name.as_str()) {
+                    (RecordType::SRV, "_minecraft._tcp.timeout.test.") => continue,
+                    (RecordType::SRV, "_minecraft._tcp.mc.test.") => vec![
+                        srv(20, 0, 25570, "backup.test."),
+                        srv(10, 1, 25566, "light.test."),
+                        srv(10, 99, 25567, "heavy.test."),
+                        srv(0, 0, 25565, "."),
+                    ],
+                    (RecordType::A, _) => vec![RData::A(A(Ipv4Addr::new(127, 0, 0, 1)))],
+                    _ => vec![],
+                };
+                for rdata in answers {
+                    response.add_answer(Record::from_rdata(query.name().clone(), 60, rdata));
+                }
+
+                let _ = socket.send_to(&response.to_vec().unwrap(), peer).await;
+            }
+        });
+
+        SrvResolver::with_nameserver_timeout(addr, Duration::from_millis(200))
+    }
+
+    #[tokio::test]
+    async fn orders_by_priority_and_weight() {
+        let resolver = stub_resolver().await;
+
+        let mut heavy_first = 0;
+        for _ in 0..100 {
+            let targets = resolver.lookup_srv("mc.test").await.unwrap();
+            let hosts: Vec<&str> = targets.iter().map(|t| t.host.as_str()).collect();
+
+            // "." is dropped, the lower priority comes last
+            assert_eq!(hosts.len(), 3);
+            assert_eq!(hosts[2], "backup.test");
+            if hosts[0] == "heavy.test" {
+                heavy_first += 1;
+            }
+        }
+
+        // 99 to 1, the light record should rarely win
+        assert!(heavy_first > 75, "heavy record first {} times", heavy_first);
+    }
+
+    #[tokio::test]
+    async fn resolves_srv_targets_and_falls_back() {
+        let resolver = stub_resolver().await;
+
+        let resolved = resolver.resolve("mc.test", DEFAULT_PORT).await.unwrap();
+        assert!([25566, 25567].contains(&resolved.addrs[0].port()));
+        assert!(resolved.srv_target.is_some());
+
+        assert!(resolver.lookup_srv("timeout.test").await.is_err());
+
+        // no records and a failing lookup both end up at the A record with the given port
+        for host in ["plain.test", "timeout.test"] {
+            let resolved = resolver.resolve(host, DEFAULT_PORT).await.unwrap();
+            assert_eq!(
+                resolved.addrs,
+                [SocketAddr::from((Ipv4Addr::LOCALHOST, DEFAULT_PORT))]
+            );
+            assert_eq!(resolved.srv_target, None);
+        }
+    }
+}
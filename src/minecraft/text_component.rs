@@ -1,24 +1,122 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use serde_with::skip_serializing_none;
+use uuid::Uuid;
 
-use super::packet::slp::SlpServerDescription;
+use super::{
+    render::markdown::MarkdownRenderer,
+    translation::{self, FormatPart, Language},
+};
+
+// arguments can be referenced several times and nested, which grows the output exponentially,
+// more than any server list entry or chat message shows is never expanded
+const TRANSLATION_BUDGET: usize = 32 * 1024;
+
+/*
+A chat component is either a string, an array or an object.
+Strings are text components, the first element of an array is the parent of the remaining ones.
+Objects name their content type in "type" since 1.20.3, before that it's inferred from the
+content fields in the order text, translate, score, selector, keybind, nbt.
+*/
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(from = "RawComponent", into = "ComponentObject")]
+pub struct TextComponent {
+    pub content: TextContent,
+    pub style: Style,
+    pub extra: Vec<TextComponent>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextContent {
+    Text(String),
+    Translatable {
+        translate: String,
+        fallback: Option<String>,
+        with: Vec<TextComponent>,
+    },
+    Score(Score),
+    Selector {
+        selector: String,
+        separator: Option<Box<TextComponent>>,
+    },
+    Keybind(String),
+    Nbt {
+        path: String,
+        interpret: bool,
+        separator: Option<Box<TextComponent>>,
+        source: NbtSource,
+    },
+}
+
+impl Default for TextContent {
+    fn default() -> Self {
+        Self::Text(String::new())
+    }
+}
 
 #[skip_serializing_none]
-#[derive(Deserialize, Serialize, Debug)]
-#[serde(rename_all = "camelCase")]
-pub enum TextComponentType {
-    Text,
-    Translatable,
-    Keybind,
-    Score,
-    Selector,
-    Nbt,
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Score {
+    pub name: String,
+    pub objective: String,
+    pub value: Option<String>, // resolved by the server before 1.16
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtSource {
+    /// Coordinates of a block entity
+    Block(String),
+    /// Selector of an entity
+    Entity(String),
+    /// Id of a command storage
+    Storage(String),
 }
 
 #[skip_serializing_none]
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Style {
+    pub color: Option<String>, // color name or #hex rgb spec
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underlined: Option<bool>,
+    pub strikethrough: Option<bool>,
+    pub obfuscated: Option<bool>,
+    pub font: Option<String>,      // only valid on 1.16+
+    pub insertion: Option<String>, // to insert text when clicked in chat
+    #[serde(alias = "click_event")]
+    pub click_event: Option<Box<ClickEvent>>,
+    #[serde(alias = "hover_event")]
+    pub hover_event: Option<Box<HoverEvent>>,
+}
+
+impl Style {
+    /// Fills the unset properties from the style of the parent component.
+    pub fn inherit(&self, parent: &Style) -> Style {
+        Style {
+            color: self.color.clone().or_else(|| parent.color.clone()),
+            bold: self.bold.or(parent.bold),
+            italic: self.italic.or(parent.italic),
+            underlined: self.underlined.or(parent.underlined),
+            strikethrough: self.strikethrough.or(parent.strikethrough),
+            obfuscated: self.obfuscated.or(parent.obfuscated),
+            font: self.font.clone().or_else(|| parent.font.clone()),
+            insertion: self.insertion.clone().or_else(|| parent.insertion.clone()),
+            click_event: self
+                .click_event
+                .clone()
+                .or_else(|| parent.click_event.clone()),
+            hover_event: self
+                .hover_event
+                .clone()
+                .or_else(|| parent.hover_event.clone()),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
-pub enum TextComponentClickAction {
+pub enum ClickAction {
     OpenUrl,
     OpenFile, // cannot be used within json chat, only exists internally to link screenshots in chat
     RunCommand,
@@ -26,88 +124,650 @@ pub enum TextComponentClickAction {
     SuggestCommand,
     ChangePage,
     CopyToClipboard,
+    ShowDialog, // 1.21.6+
+    Custom,     // 1.21.6+
+    #[serde(other)]
+    Unknown,
 }
 
-#[skip_serializing_none]
-#[derive(Deserialize, Serialize, Debug)]
-#[serde(rename_all = "camelCase")]
-pub struct TextComponentClick {
-    pub action: TextComponentClickAction,
+/// Serialized in the pre 1.21.5 form with a single value.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(from = "RawClickEvent")]
+pub struct ClickEvent {
+    pub action: ClickAction,
     pub value: String,
 }
 
+// since 1.21.5 the value is named after the action
+#[derive(Deserialize)]
+struct RawClickEvent {
+    action: ClickAction,
+    #[serde(alias = "url", alias = "command", alias = "path", alias = "id")]
+    value: Option<Value>,
+    page: Option<Value>,
+}
+
+impl From<RawClickEvent> for ClickEvent {
+    fn from(value: RawClickEvent) -> Self {
+        let value_string = match value.value.or(value.page) {
+            Some(Value::String(s)) => s,
+            Some(other) => other.to_string(),
+            None => String::new(),
+        };
+
+        Self {
+            action: value.action,
+            value: value_string,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(from = "RawHoverEvent", into = "RawHoverEvent")]
+pub enum HoverEvent {
+    ShowText(Box<TextComponent>),
+    ShowItem {
+        id: String,
+        count: Option<i32>,
+        /// item components (1.20.5+) or the nbt tag as SNBT
+        components: Option<Value>,
+    },
+    ShowEntity {
+        entity_type: String,
+        uuid: Option<Uuid>,
+        name: Option<Box<TextComponent>>,
+    },
+    /// Removed actions like show_achievement and the SNBT `value` form of items and entities
+    Other {
+        action: String,
+        value: Value,
+    },
+}
+
+/*
+Hover events carry their data in "contents" since 1.16 and in "value" before,
+1.21.5 moved the fields of items and entities into the event itself.
+*/
 #[skip_serializing_none]
-#[derive(Deserialize, Serialize, Debug)]
-#[serde(rename_all = "camelCase")]
-pub struct TextComponent {
-    #[serde(rename = "type")]
-    pub type_: Option<TextComponentType>,
-    pub extra: Option<Vec<SlpServerDescription>>,
+#[derive(Deserialize, Serialize)]
+struct RawHoverEvent {
+    action: String,
+    contents: Option<Value>,
+    value: Option<Value>,
+    #[serde(flatten)]
+    inline: serde_json::Map<String, Value>,
+}
 
-    // Type: text
-    pub text: String,
+fn parse_uuid(value: &Value) -> Option<Uuid> {
+    match value {
+        Value::String(s) => Uuid::parse_str(s).ok(),
+        // four big endian ints
+        Value::Array(parts) if parts.len() == 4 => {
+            let mut uuid = 0u128;
+            for part in parts {
+                uuid = (uuid << 32) | (part.as_i64()? as u32 as u128);
+            }
+            Some(Uuid::from_u128(uuid))
+        }
+        _ => None,
+    }
+}
 
-    // Styling fields
-    pub color: Option<String>, // color name or #hex rgb spec
-    pub bold: Option<bool>,
-    pub italic: Option<bool>,
-    pub underlined: Option<bool>,
-    pub strikethrough: Option<bool>,
-    pub obfuscated: Option<bool>,
-    pub font: Option<String>, // only valid on 1.16+
-    pub insertion: Option<String>, // to insert text when clicked in chat
-                              // pub click_event: Option<TextComponentClick>,
+fn parse_component(value: Value) -> Option<TextComponent> {
+    serde_json::from_value(value).ok()
 }
 
-impl TextComponent {
-    pub fn format_string(&self) -> String {
-        match self.type_ {
-            Some(TextComponentType::Text) => {}
-            None => {}
-            _ => return "".into(),
+impl From<RawHoverEvent> for HoverEvent {
+    fn from(value: RawHoverEvent) -> Self {
+        let RawHoverEvent {
+            action,
+            contents,
+            value,
+            inline,
+        } = value;
+
+        let fields = match contents {
+            Some(Value::Object(fields)) => fields,
+            Some(contents) if action == "show_text" => {
+                return match parse_component(contents.clone()) {
+                    Some(text) => Self::ShowText(Box::new(text)),
+                    None => Self::Other {
+                        action,
+                        value: contents,
+                    },
+                }
+            }
+            // items may be given by their id alone
+            Some(Value::String(id)) if action == "show_item" => {
+                return Self::ShowItem {
+                    id,
+                    count: None,
+                    components: None,
+                }
+            }
+            _ if !inline.is_empty() => inline,
+            _ => serde_json::Map::new(),
+        };
+
+        match action.as_str() {
+            "show_text" => {
+                let text = value
+                    .or_else(|| Some(Value::Object(fields.clone())))
+                    .and_then(parse_component);
+
+                match text {
+                    Some(text) => Self::ShowText(Box::new(text)),
+                    None => Self::Other {
+                        action,
+                        value: Value::Object(fields),
+                    },
+                }
+            }
+            "show_item" if fields.contains_key("id") => Self::ShowItem {
+                id: fields["id"].as_str().unwrap_or_default().to_string(),
+                count: fields
+                    .get("count")
+                    .and_then(Value::as_i64)
+                    .map(|c| c as i32),
+                components: fields
+                    .get("components")
+                    .or_else(|| fields.get("tag"))
+                    .cloned(),
+            },
+            "show_entity" if fields.contains_key("type") || fields.contains_key("id") => {
+                // "id" is the entity type since 1.21.5 and the uuid before
+                let (entity_type, uuid) = match fields.get("type") {
+                    Some(entity_type) => (entity_type, fields.get("id")),
+                    None => (&fields["id"], fields.get("uuid")),
+                };
+
+                Self::ShowEntity {
+                    entity_type: entity_type.as_str().unwrap_or_default().to_string(),
+                    uuid: uuid.and_then(parse_uuid),
+                    name: fields
+                        .get("name")
+                        .cloned()
+                        .and_then(parse_component)
+                        .map(Box::new),
+                }
+            }
+            _ => Self::Other {
+                action,
+                value: value.unwrap_or(Value::Object(fields)),
+            },
+        }
+    }
+}
+
+impl From<HoverEvent> for RawHoverEvent {
+    fn from(value: HoverEvent) -> Self {
+        let (action, contents) = match value {
+            HoverEvent::ShowText(text) => (
+                "show_text".to_string(),
+                serde_json::to_value(text).unwrap_or_default(),
+            ),
+            HoverEvent::ShowItem {
+                id,
+                count,
+                components,
+            } => {
+                let mut fields = serde_json::Map::new();
+                fields.insert("id".into(), id.into());
+                if let Some(count) = count {
+                    fields.insert("count".into(), count.into());
+                }
+                if let Some(components) = components {
+                    fields.insert("components".into(), components);
+                }
+                ("show_item".to_string(), Value::Object(fields))
+            }
+            HoverEvent::ShowEntity {
+                entity_type,
+                uuid,
+                name,
+            } => {
+                let mut fields = serde_json::Map::new();
+                fields.insert("type".into(), entity_type.into());
+                if let Some(uuid) = uuid {
+                    fields.insert("id".into(), uuid.hyphenated().to_string().into());
+                }
+                if let Some(name) = name {
+                    fields.insert(
+                        "name".into(),
+                        serde_json::to_value(name).unwrap_or_default(),
+                    );
+                }
+                ("show_entity".to_string(), Value::Object(fields))
+            }
+            HoverEvent::Other { action, value } => {
+                return Self {
+                    action,
+                    contents: None,
+                    value: Some(value),
+                    inline: serde_json::Map::new(),
+                }
+            }
         };
 
-        let mut res = self.text.clone();
+        Self {
+            action,
+            contents: Some(contents),
+            value: None,
+            inline: serde_json::Map::new(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawComponent {
+    String(String),
+    Array(Vec<TextComponent>),
+    Object(Box<ComponentObject>),
+    // "with" arguments may be plain numbers and booleans since 1.20.3
+    Number(serde_json::Number),
+    Bool(bool),
+}
+
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Default)]
+struct ComponentObject {
+    #[serde(rename = "type")]
+    type_: Option<String>,
+
+    text: Option<String>,
 
-        let mut is_wrapped = false;
-        let mut wrap_str = |chars| {
-            res.insert_str(0, chars);
-            res.push_str(chars);
-            is_wrapped = true;
+    translate: Option<String>,
+    fallback: Option<String>,
+    with: Option<Vec<TextComponent>>,
+
+    score: Option<Score>,
+
+    selector: Option<String>,
+    separator: Option<Box<TextComponent>>,
+
+    keybind: Option<String>,
+
+    nbt: Option<String>,
+    interpret: Option<bool>,
+    source: Option<String>,
+    block: Option<String>,
+    entity: Option<String>,
+    storage: Option<String>,
+
+    #[serde(flatten)]
+    style: Style,
+
+    extra: Option<Vec<TextComponent>>,
+}
+
+impl ComponentObject {
+    fn content_type(&self) -> &str {
+        if let Some(ref type_) = self.type_ {
+            return type_;
+        }
+
+        match self {
+            _ if self.text.is_some() => "text",
+            _ if self.translate.is_some() => "translatable",
+            _ if self.score.is_some() => "score",
+            _ if self.selector.is_some() => "selector",
+            _ if self.keybind.is_some() => "keybind",
+            _ if self.nbt.is_some() => "nbt",
+            _ => "text",
+        }
+    }
+
+    fn nbt_source(&mut self) -> NbtSource {
+        let explicit = self.source.take();
+
+        match (explicit.as_deref(), self.block.take(), self.entity.take()) {
+            (Some("block") | None, Some(block), _) => NbtSource::Block(block),
+            (Some("entity") | None, _, Some(entity)) => NbtSource::Entity(entity),
+            _ => NbtSource::Storage(self.storage.take().unwrap_or_default()),
+        }
+    }
+}
+
+impl From<ComponentObject> for TextComponent {
+    fn from(mut value: ComponentObject) -> Self {
+        let content = match value.content_type() {
+            "translatable" => TextContent::Translatable {
+                translate: value.translate.take().unwrap_or_default(),
+                fallback: value.fallback.take(),
+                with: value.with.take().unwrap_or_default(),
+            },
+            "score" if value.score.is_some() => TextContent::Score(value.score.take().unwrap()),
+            "selector" => TextContent::Selector {
+                selector: value.selector.take().unwrap_or_default(),
+                separator: value.separator.take(),
+            },
+            "keybind" => TextContent::Keybind(value.keybind.take().unwrap_or_default()),
+            "nbt" => TextContent::Nbt {
+                path: value.nbt.take().unwrap_or_default(),
+                interpret: value.interpret.unwrap_or_default(),
+                separator: value.separator.take(),
+                source: value.nbt_source(),
+            },
+            // unknown types are shown as text, like components without content
+            _ => TextContent::Text(value.text.take().unwrap_or_default()),
         };
 
-        let is_true = |arg: &Option<bool>| arg.is_some_and(|x| x == true);
+        Self {
+            content,
+            style: value.style,
+            extra: value.extra.unwrap_or_default(),
+        }
+    }
+}
 
-        if is_true(&self.bold) && is_true(&self.italic) {
-            wrap_str("***");
-        } else if is_true(&self.bold) {
-            wrap_str("**");
-        } else if is_true(&self.italic) {
-            wrap_str("*");
+impl From<RawComponent> for TextComponent {
+    fn from(value: RawComponent) -> Self {
+        match value {
+            RawComponent::String(text) => Self::text(text),
+            RawComponent::Array(mut components) => {
+                if components.is_empty() {
+                    return Self::default();
+                }
+
+                let mut parent = components.remove(0);
+                parent.extra.extend(components);
+                parent
+            }
+            RawComponent::Object(object) => (*object).into(),
+            RawComponent::Number(number) => Self::text(number.to_string()),
+            RawComponent::Bool(value) => Self::text(value.to_string()),
         }
+    }
+}
+
+impl From<TextComponent> for ComponentObject {
+    fn from(value: TextComponent) -> Self {
+        let mut object = ComponentObject {
+            style: value.style,
+            extra: Some(value.extra).filter(|e| !e.is_empty()),
+            ..Default::default()
+        };
 
-        if is_true(&self.underlined) {
-            wrap_str("__");
+        match value.content {
+            TextContent::Text(text) => object.text = Some(text),
+            TextContent::Translatable {
+                translate,
+                fallback,
+                with,
+            } => {
+                object.translate = Some(translate);
+                object.fallback = fallback;
+                object.with = Some(with).filter(|w| !w.is_empty());
+            }
+            TextContent::Score(score) => object.score = Some(score),
+            TextContent::Selector {
+                selector,
+                separator,
+            } => {
+                object.selector = Some(selector);
+                object.separator = separator;
+            }
+            TextContent::Keybind(keybind) => object.keybind = Some(keybind),
+            TextContent::Nbt {
+                path,
+                interpret,
+                separator,
+                source,
+            } => {
+                object.nbt = Some(path);
+                object.interpret = Some(interpret).filter(|i| *i);
+                object.separator = separator;
+                match source {
+                    NbtSource::Block(block) => object.block = Some(block),
+                    NbtSource::Entity(entity) => object.entity = Some(entity),
+                    NbtSource::Storage(storage) => object.storage = Some(storage),
+                }
+            }
         }
 
-        if is_true(&self.strikethrough) {
-            wrap_str("~~");
+        object
+    }
+}
+
+impl TextComponent {
+    pub fn text<T: Into<String>>(text: T) -> Self {
+        Self {
+            content: TextContent::Text(text.into()),
+            ..Default::default()
         }
+    }
 
-        if is_wrapped {
-            res.push('\u{200B}'); // zero width to seperate markdown
+    /// The text shown for the content itself, without the children.
+    /// Translations are not resolved here, see [`TextComponent::translated`], the fallback or
+    /// the key is shown instead. Server side content like selectors can't be resolved either.
+    pub fn content_text(&self) -> String {
+        match self.content {
+            TextContent::Text(ref text) => text.clone(),
+            TextContent::Translatable {
+                ref translate,
+                ref fallback,
+                ..
+            } => fallback.as_ref().unwrap_or(translate).clone(),
+            TextContent::Score(ref score) => score.value.clone().unwrap_or_default(),
+            TextContent::Selector { ref selector, .. } => selector.clone(),
+            TextContent::Keybind(ref keybind) => keybind.clone(),
+            TextContent::Nbt { .. } => String::new(),
         }
+    }
 
-        if let Some(childs) = &self.extra {
-            for child in childs {
-                let child_string = match child {
-                    SlpServerDescription::Simple(x) => x.clone(),
-                    SlpServerDescription::Complex(x) => x.format_string(),
-                };
-                res.push_str(child_string.as_str());
+    /// Resolves translatable and keybind content in the given language.
+    /// Translations become text components with the literals and arguments as children,
+    /// so arguments keep their own style on top of the one of the translation.
+    pub fn translated(&self, language: Language) -> TextComponent {
+        let mut budget = TRANSLATION_BUDGET;
+        self.translated_within(language, &mut budget)
+    }
+
+    // every component and character of text uses up the budget, once it's gone the rest is left out
+    fn translated_within(&self, language: Language, budget: &mut usize) -> TextComponent {
+        *budget = budget.saturating_sub(1);
+        let mut extra = Vec::new();
+
+        let content = match self.content {
+            TextContent::Translatable {
+                ref translate,
+                ref fallback,
+                ref with,
+            } => {
+                let format = language
+                    .translate(translate)
+                    .or(fallback.as_deref())
+                    .unwrap_or(translate);
+
+                match translation::parse_format(format, with.len()) {
+                    Some(parts) => {
+                        for part in parts {
+                            if *budget == 0 {
+                                break;
+                            }
+
+                            extra.push(match part {
+                                FormatPart::Literal(literal) => {
+                                    *budget = budget.saturating_sub(literal.len());
+                                    TextComponent::text(literal)
+                                }
+                                FormatPart::Argument(index) => {
+                                    with[index].translated_within(language, budget)
+                                }
+                            });
+                        }
+                        TextContent::default()
+                    }
+                    None => {
+                        *budget = budget.saturating_sub(format.len());
+                        TextContent::Text(format.to_string())
+                    }
+                }
+            }
+            TextContent::Keybind(ref keybind) => {
+                TextContent::Text(language.translate(keybind).unwrap_or(keybind).to_string())
+            }
+            TextContent::Text(ref text) => {
+                *budget = budget.saturating_sub(text.len());
+                TextContent::Text(text.clone())
+            }
+            ref content => content.clone(),
+        };
+
+        for child in &self.extra {
+            if *budget == 0 {
+                break;
             }
+            extra.push(child.translated_within(language, budget));
+        }
+
+        TextComponent {
+            content,
+            style: self.style.clone(),
+            extra,
+        }
+    }
+
+    pub fn format_string(&self) -> String {
+        self.format_string_in(Language::default())
+    }
+
+    /// Discord markdown, see [`MarkdownRenderer`].
+    pub fn format_string_in(&self, language: Language) -> String {
+        MarkdownRenderer::new().language(language).render(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> TextComponent {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn shorthand_forms() {
+        assert_eq!(parse(r#""hello""#), TextComponent::text("hello"));
+
+        let array = parse(r#"["a", {"text": "b", "bold": true}, "c"]"#);
+        assert_eq!(array.content, TextContent::Text("a".into()));
+        assert_eq!(array.extra.len(), 2);
+        assert_eq!(array.extra[0].style.bold, Some(true));
+        assert_eq!(array.format_string(), "a**b**c");
+    }
+
+    #[test]
+    fn explicit_and_inferred_type() {
+        let inferred = parse(r#"{"translate": "chat.type.text", "with": ["Steve", 42]}"#);
+        let explicit = parse(
+            r#"{"type": "translatable", "translate": "chat.type.text", "with": ["Steve", 42]}"#,
+        );
+        assert_eq!(inferred, explicit);
+
+        let TextContent::Translatable { ref with, .. } = inferred.content else {
+            panic!("expected a translatable component");
+        };
+        assert_eq!(with[1], TextComponent::text("42"));
+
+        let nbt = parse(r#"{"nbt": "Items[0]", "entity": "@p", "interpret": true}"#);
+        assert!(matches!(
+            nbt.content,
+            TextContent::Nbt { source: NbtSource::Entity(ref e), interpret: true, .. } if e == "@p"
+        ));
+
+        // the explicit type wins over the other content fields
+        let keybind = parse(r#"{"type": "keybind", "text": "ignored", "keybind": "key.jump"}"#);
+        assert_eq!(keybind.content, TextContent::Keybind("key.jump".into()));
+    }
+
+    #[test]
+    fn translations() {
+        let kick = parse(
+            r#"{"translate": "multiplayer.disconnect.outdated_client", "with": [{"text": "1.20.4", "bold": true}]}"#,
+        );
+        assert_eq!(
+            kick.format_string(),
+            "Incompatible client! Please use **1.20.4**"
+        );
+        assert_eq!(
+            kick.format_string_in(Language::DeDe),
+            "Inkompatibler Client! Bitte verwende **1.20.4**"
+        );
+
+        let unknown = parse(r#"{"translate": "custom.key", "fallback": "%s!", "with": ["hi"]}"#);
+        assert_eq!(unknown.format_string(), "hi!");
+
+        let keybind = parse(r#"["Press ", {"keybind": "key.jump"}]"#);
+        assert_eq!(keybind.format_string(), "Press Jump");
+    }
+
+    #[test]
+    fn events() {
+        let legacy = parse(
+            r#"{"text": "", "clickEvent": {"action": "open_url", "value": "https://example.com"},
+                "hoverEvent": {"action": "show_text", "value": "tip"}}"#,
+        );
+        let modern = parse(
+            r#"{"text": "", "click_event": {"action": "open_url", "url": "https://example.com"},
+                "hover_event": {"action": "show_text", "value": "tip"}}"#,
+        );
+        assert_eq!(legacy, modern);
+        assert_eq!(
+            legacy.style.hover_event,
+            Some(Box::new(HoverEvent::ShowText(Box::new(
+                TextComponent::text("tip")
+            ))))
+        );
+
+        let entity = parse(
+            r#"{"text": "", "hoverEvent": {"action": "show_entity", "contents":
+                {"type": "minecraft:pig", "id": [0, 0, 0, 1], "name": "Babe"}}}"#,
+        );
+        assert_eq!(
+            entity.style.hover_event.as_deref(),
+            Some(&HoverEvent::ShowEntity {
+                entity_type: "minecraft:pig".into(),
+                uuid: Some(Uuid::from_u128(1)),
+                name: Some(Box::new(TextComponent::text("Babe"))),
+            })
+        );
+
+        // serializing and parsing again keeps the component intact
+        let json = serde_json::to_string(&entity).unwrap();
+        assert_eq!(parse(&json), entity);
+    }
+
+    #[test]
+    fn translation_bomb() {
+        fn text_length(component: &TextComponent) -> usize {
+            component.content_text().len() + component.extra.iter().map(text_length).sum::<usize>()
         }
 
-        res
+        // 8^12 copies of the innermost text without a budget
+        let fallback = "%1$s".repeat(translation::MAX_ARGUMENT_REFERENCES);
+        let mut json = r#""bomb""#.to_string();
+        for _ in 0..12 {
+            json = format!(
+                r#"{{"translate": "x", "fallback": "{}", "with": [{}]}}"#,
+                fallback, json
+            );
+        }
+        let limits = crate::minecraft::limits::Limits::default();
+        assert!(limits.check_json_depth(&json).is_ok());
+
+        let translated = parse(&json).translated(Language::EnUs);
+        assert!(text_length(&translated) <= TRANSLATION_BUDGET);
+
+        // one reference too many and the format is shown as is
+        let fallback = "%1$s".repeat(translation::MAX_ARGUMENT_REFERENCES + 1);
+        let component = parse(&format!(
+            r#"{{"translate": "x", "fallback": "{}", "with": ["a"]}}"#,
+            fallback
+        ));
+        let translated = component.translated(Language::EnUs);
+        assert_eq!(translated.content, TextContent::Text(fallback));
+        assert!(translated.extra.is_empty());
     }
 }
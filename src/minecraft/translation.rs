@@ -0,0 +1,173 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+/*
+The bundled language files are subsets of the vanilla ones,
+covering the keys servers send in status responses, disconnect messages and chat.
+Building with MINECRAFT_LANG_DIR set to a directory holding the vanilla
+en_us.json and de_de.json embeds those instead, see build.rs.
+*/
+static EN_US: LazyLock<HashMap<String, String>> =
+    LazyLock::new(|| parse(include_str!(concat!(env!("OUT_DIR"), "/lang/en_us.json"))));
+static DE_DE: LazyLock<HashMap<String, String>> =
+    LazyLock::new(|| parse(include_str!(concat!(env!("OUT_DIR"), "/lang/de_de.json"))));
+
+fn parse(json: &str) -> HashMap<String, String> {
+    serde_json::from_str(json).expect("bundled language file is invalid")
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    EnUs,
+    DeDe,
+}
+
+impl Language {
+    /// Picks the language for a locale like discords "de" or minecrafts "de_de",
+    /// falling back to english.
+    pub fn from_locale(locale: &str) -> Self {
+        match locale.get(..2).map(|l| l.to_ascii_lowercase()).as_deref() {
+            Some("de") => Self::DeDe,
+            _ => Self::EnUs,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::EnUs => "en_us",
+            Self::DeDe => "de_de",
+        }
+    }
+
+    fn table(&self) -> &'static HashMap<String, String> {
+        match self {
+            Self::EnUs => &EN_US,
+            Self::DeDe => &DE_DE,
+        }
+    }
+
+    /// Looks up a translation key, keys missing in other languages fall back to english like on the client.
+    pub fn translate(&self, key: &str) -> Option<&'static str> {
+        self.table()
+            .get(key)
+            .or_else(|| EN_US.get(key))
+            .map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatPart {
+    Literal(String),
+    /// Index into the translation arguments
+    Argument(usize),
+}
+
+/// Real translations use an argument once or twice, servers may repeat one to blow up the output.
+pub const MAX_ARGUMENT_REFERENCES: usize = 8;
+
+/// Splits a translation into literals and arguments, supporting `%s`, `%1$s` and `%%`.
+/// Returns `None` for invalid format strings, arguments out of range or referenced
+/// more than [`MAX_ARGUMENT_REFERENCES`] times, the client shows the plain format string then.
+pub fn parse_format(format: &str, arguments: usize) -> Option<Vec<FormatPart>> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut next_argument = 0;
+    let mut references = vec![0; arguments];
+
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            literal.push(c);
+            continue;
+        }
+
+        let mut digits = String::new();
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            digits.push(digit);
+        }
+
+        let index = match (digits.is_empty(), chars.next()) {
+            (true, Some('%')) => {
+                literal.push('%');
+                continue;
+            }
+            (true, Some('s')) => {
+                next_argument += 1;
+                next_argument - 1
+            }
+            (false, Some('$')) if chars.next() == Some('s') => {
+                digits.parse::<usize>().ok()?.checked_sub(1)?
+            }
+            _ => return None,
+        };
+
+        let count = references.get_mut(index)?;
+        *count += 1;
+        if *count > MAX_ARGUMENT_REFERENCES {
+            return None;
+        }
+
+        if !literal.is_empty() {
+            parts.push(FormatPart::Literal(std::mem::take(&mut literal)));
+        }
+        parts.push(FormatPart::Argument(index));
+    }
+
+    if !literal.is_empty() {
+        parts.push(FormatPart::Literal(literal));
+    }
+
+    Some(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(key: &str, arguments: &[&str]) -> Option<String> {
+        let format = Language::EnUs.translate(key)?;
+        let parts = parse_format(format, arguments.len())?;
+
+        Some(
+            parts
+                .into_iter()
+                .map(|part| match part {
+                    FormatPart::Literal(literal) => literal,
+                    FormatPart::Argument(index) => arguments[index].to_string(),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn vanilla_test_keys() {
+        assert_eq!(
+            render("translation.test.none", &[]).as_deref(),
+            Some("Hello, world!")
+        );
+        assert_eq!(
+            render("translation.test.complex", &["a", "b", "c", "d"]).as_deref(),
+            Some("Prefix, ab again b and a lastly c and also a again!")
+        );
+        assert_eq!(
+            render("translation.test.escape", &["a", "b"]).as_deref(),
+            Some("%s %a %%s %%b")
+        );
+        assert_eq!(render("translation.test.invalid", &[]), None);
+        assert_eq!(render("translation.test.invalid2", &[]), None);
+        assert_eq!(render("translation.test.args", &["only one"]), None);
+    }
+
+    #[test]
+    fn german_falls_back_to_english() {
+        assert_eq!(
+            Language::from_locale("de").translate("multiplayer.disconnect.server_full"),
+            Some("Der Server ist voll!")
+        );
+        assert_eq!(
+            Language::DeDe.translate("translation.test.none"),
+            Some("Hello, world!")
+        );
+        assert_eq!(Language::from_locale("en-US"), Language::EnUs);
+    }
+}
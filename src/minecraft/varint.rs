@@ -1,28 +1,26 @@
 use std::io::{self, Read};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct VarInt(pub i32);
 
 impl VarInt {
     const SEGMENT_BITS: u8 = 0b0111_1111;
     const CONTINUE_BIT: u8 = 0b1000_0000;
 
-    pub fn from_bytes<R: Read>(reader: R) -> io::Result<Self> {
+    pub fn from_bytes<R: Read>(mut reader: R) -> io::Result<Self> {
         let mut value: i32 = 0;
 
         let mut position = 0;
 
-        let mut bytes = reader.bytes();
         loop {
-            let current_byte = match bytes.next() {
-                Some(x) => x?,
-                None => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        "Invalid input buffer",
-                    ))
-                }
-            };
+            let mut byte = [0; 1];
+            if reader.read(&mut byte)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Invalid input buffer",
+                ));
+            }
+            let current_byte = byte[0];
 
             value |= ((current_byte & Self::SEGMENT_BITS) as i32) << position;
 
@@ -43,6 +41,31 @@ impl VarInt {
         Ok(Self(value))
     }
 
+    /// Decodes a VarInt from the start of a buffer that may not hold all of its bytes yet.
+    /// Returns the value and its encoded size, or `None` if more bytes are needed.
+    pub fn from_partial(buf: &[u8]) -> io::Result<Option<(Self, usize)>> {
+        let mut value: i32 = 0;
+
+        for (index, current_byte) in buf.iter().enumerate() {
+            let position = index * 7;
+
+            if position >= 32 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "VarInt is too big",
+                ));
+            }
+
+            value |= ((current_byte & Self::SEGMENT_BITS) as i32) << position;
+
+            if current_byte & Self::CONTINUE_BIT == 0 {
+                return Ok(Some((Self(value), index + 1)));
+            }
+        }
+
+        Ok(None)
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut result = Vec::new();
         let mut value = self.0 as u32;
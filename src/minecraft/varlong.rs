@@ -0,0 +1,63 @@
+use std::io::{self, Read};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VarLong(pub i64);
+
+impl VarLong {
+    const SEGMENT_BITS: u8 = 0b0111_1111;
+    const CONTINUE_BIT: u8 = 0b1000_0000;
+
+    pub fn from_bytes<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut value: i64 = 0;
+
+        let mut position = 0;
+
+        loop {
+            let mut byte = [0; 1];
+            if reader.read(&mut byte)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Invalid input buffer",
+                ));
+            }
+            let current_byte = byte[0];
+
+            value |= ((current_byte & Self::SEGMENT_BITS) as i64) << position;
+
+            if current_byte & Self::CONTINUE_BIT == 0 {
+                break;
+            }
+
+            position += 7;
+
+            if position >= 64 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "VarLong is too big",
+                ));
+            }
+        }
+
+        Ok(Self(value))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        let mut value = self.0 as u64;
+
+        loop {
+            let mut byte = (value & Self::SEGMENT_BITS as u64) as u8;
+            value >>= 7;
+            if value != 0 {
+                byte |= Self::CONTINUE_BIT;
+            }
+            result.push(byte);
+
+            if value == 0 {
+                break;
+            }
+        }
+
+        result
+    }
+}